@@ -0,0 +1,43 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use super::{Event, WaitAsyncStd, WaitOutcome};
+
+/// Future returned by `Event::wait_async_cancellable`.
+pub struct WaitAsyncCancellable<'a, F> {
+    wait: WaitAsyncStd<'a>,
+    cancel: Pin<Box<F>>,
+}
+
+impl Event {
+    /// Async counterpart of `wait_cancellable`: resolves to `Signaled` once
+    /// the event is signaled, or to `Cancelled` once `cancel` completes,
+    /// whichever happens first. The event is polled first, so a signal that
+    /// is already pending wins over a cancellation that is already due.
+    pub fn wait_async_cancellable<F>(&self, cancel: F) ->
+        WaitAsyncCancellable<'_, F>
+        where F: Future<Output = ()>
+    {
+        WaitAsyncCancellable {
+            wait: self.wait_async_std(),
+            cancel: Box::pin(cancel),
+        }
+    }
+}
+
+impl<'a, F> Future for WaitAsyncCancellable<'a, F>
+    where F: Future<Output = ()>
+{
+    type Output = WaitOutcome;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<WaitOutcome> {
+        if Pin::new(&mut self.wait).poll(cx).is_ready() {
+            return Poll::Ready(WaitOutcome::Signaled);
+        };
+        if self.cancel.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(WaitOutcome::Cancelled);
+        };
+        Poll::Pending
+    }
+}
@@ -0,0 +1,56 @@
+// Memory footprint of a million single-use events, each notified and waited
+// on once. Run with `cargo bench --bench footprint`.
+
+extern crate event_object;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use event_object::Event;
+
+const EVENTS: usize = 1_000_000;
+
+// Counts the bytes currently allocated.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn main() {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let events: Vec<Event> = (0..EVENTS).map(|_| {
+        let event = Event::new(false, true).unwrap();
+        event.notify();
+        event.wait();
+        event
+    }).collect();
+    let elapsed = start.elapsed();
+    let bytes = (ALLOCATED.load(Ordering::Relaxed) - before) / EVENTS;
+    let inline = mem::size_of::<Event>();
+    println!(
+        "{} events: {} bytes each, {} inline and {} on the heap, in {:?}",
+        EVENTS,
+        bytes,
+        inline,
+        bytes - inline,
+        elapsed
+    );
+    drop(events);
+}
@@ -0,0 +1,50 @@
+use std::sync::{Condvar, Mutex};
+
+/// A counting event with backpressure: each `notify` adds one pending
+/// signal and each `wait` consumes one, but `notify` blocks while `capacity`
+/// signals are already pending, until a waiter makes room.
+pub struct BoundedEvent {
+    pending: Mutex<usize>,
+    capacity: usize,
+    // Signaled when a signal is added, for waiters.
+    not_empty: Condvar,
+    // Signaled when a signal is consumed, for blocked notifiers.
+    not_full: Condvar,
+}
+
+impl BoundedEvent {
+    pub fn new(capacity: usize) -> Self {
+        if capacity == 0 {
+            panic!("The capacity must be at least one.");
+        };
+        BoundedEvent {
+            pending: Mutex::new(0),
+            capacity: capacity,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    pub fn notify(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        while *pending == self.capacity {
+            pending = self.not_full.wait(pending).unwrap();
+        };
+        *pending += 1;
+        self.not_empty.notify_one();
+    }
+
+    pub fn wait(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        while *pending == 0 {
+            pending = self.not_empty.wait(pending).unwrap();
+        };
+        *pending -= 1;
+        self.not_full.notify_one();
+    }
+
+    /// How many signals are pending, not yet consumed by `wait`.
+    pub fn pending(&self) -> usize {
+        *self.pending.lock().unwrap()
+    }
+}
@@ -0,0 +1,91 @@
+use super::Event;
+
+/// Configures an `Event` before creating it, for the options that don't fit
+/// `Event::new`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EventBuilder {
+    initial_signaled: bool,
+    auto_reset: bool,
+    stats: bool,
+    track_max_wait: bool,
+    track_notify_intervals: bool,
+    track_waiter_locations: bool,
+    waiter_capacity: usize,
+}
+
+impl EventBuilder {
+    pub fn new() -> Self {
+        EventBuilder::default()
+    }
+
+    pub fn initial_signaled(mut self, initial_signaled: bool) -> Self {
+        self.initial_signaled = initial_signaled;
+        self
+    }
+
+    pub fn auto_reset(mut self, auto_reset: bool) -> Self {
+        self.auto_reset = auto_reset;
+        self
+    }
+
+    /// Keeps the aggregate counters reported by `Event::stats`. Off by
+    /// default; when enabled, each wait and notify costs a few extra atomic
+    /// operations.
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Keeps the longest single wait, reported by `Event::max_wait_duration`.
+    /// Cheaper than full `stats`: one extra atomic operation per wait.
+    pub fn track_max_wait(mut self, track_max_wait: bool) -> Self {
+        self.track_max_wait = track_max_wait;
+        self
+    }
+
+    /// Keeps a moving average of the time between notifies, for
+    /// `Event::estimated_time_to_next`. Costs a lock and a clock read per
+    /// notify.
+    pub fn track_notify_intervals(mut self, track: bool) -> Self {
+        self.track_notify_intervals = track;
+        self
+    }
+
+    /// Records where each plain wait is called from, for
+    /// `Event::last_waiter_location`. Costs a lock per wait.
+    pub fn track_waiter_locations(mut self, track: bool) -> Self {
+        self.track_waiter_locations = track;
+        self
+    }
+
+    /// Preallocates room for `waiter_capacity` concurrent multi-wait
+    /// registrations, so events known to take part in large `wait_for_all`
+    /// sets don't reallocate while waiters register. No effect on Windows,
+    /// where the kernel tracks waiters.
+    pub fn waiter_capacity(mut self, waiter_capacity: usize) -> Self {
+        self.waiter_capacity = waiter_capacity;
+        self
+    }
+
+    // Mirrors the error type of `Event::new`.
+    #[allow(clippy::result_unit_err)]
+    pub fn build(self) -> Result<Event, ()> {
+        let mut event = Event::new(self.initial_signaled, self.auto_reset)?;
+        if self.stats {
+            event.enable_stats();
+        };
+        if self.track_max_wait {
+            event.track_max_wait();
+        };
+        if self.track_notify_intervals {
+            event.track_notify_intervals();
+        };
+        if self.track_waiter_locations {
+            event.track_waiter_locations();
+        };
+        if self.waiter_capacity > 0 {
+            event.reserve_waiters(self.waiter_capacity);
+        };
+        Ok(event)
+    }
+}
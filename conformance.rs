@@ -0,0 +1,167 @@
+// Behavioral contract shared by the generic and Windows backends. Everything
+// here goes through the public API only and must pass on both; where the
+// platforms legitimately differ, the test branches and says why.
+
+extern crate crossbeam;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Event, WaitError, try_wait_for_any, try_wait_for_all};
+use super::{wait_for_any, wait_for_all, wait_for_any_with, wait_for_all_with};
+use super::{wait_for_any_until, wait_for_all_until};
+
+// How early a timed wait may return. `WaitForSingleObject` and friends count
+// in whole system timer ticks and may expire up to one tick (about 15.6ms by
+// default) before the requested time. The generic backend waits on
+// `Instant` deadlines and never returns early.
+#[cfg(windows)]
+const TIMEOUT_SLACK: Duration = Duration::from_millis(16);
+#[cfg(not(windows))]
+const TIMEOUT_SLACK: Duration = Duration::from_millis(0);
+
+fn new_events(count: usize, auto_reset: bool) -> Vec<Arc<Event>> {
+    (0..count)
+        .map(|_| Arc::new(Event::new(false, auto_reset).unwrap()))
+        .collect()
+}
+
+fn panics<F: FnOnce() -> R, R>(f: F) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(f)).is_err()
+}
+
+#[test]
+fn notify_wakes_waiter() {
+    for &auto_reset in &[false, true] {
+        let event = Event::new(false, auto_reset).unwrap();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                event.notify();
+            });
+            event.wait();
+        });
+    };
+}
+
+#[test]
+fn auto_reset_consumes_exactly_one_signal() {
+    let event = Event::new(false, true).unwrap();
+    event.notify();
+    event.notify();
+    assert!(event.try_wait());
+    assert!(!event.try_wait());
+
+    event.notify();
+    event.wait();
+    assert!(!event.try_wait());
+
+    event.notify();
+    assert!(!event.wait_for(Duration::from_millis(10)).timed_out());
+    assert!(event.wait_for(Duration::from_millis(10)).timed_out());
+}
+
+#[test]
+fn manual_reset_stays_signaled() {
+    let event = Event::new(true, false).unwrap();
+    event.wait();
+    assert!(event.try_wait());
+    assert!(!event.wait_for(Duration::from_millis(10)).timed_out());
+    event.unnotify();
+    assert!(!event.try_wait());
+}
+
+#[test]
+fn wait_for_times_out() {
+    let event = Event::new(false, false).unwrap();
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    assert!(event.wait_for(timeout).timed_out());
+    assert!(start.elapsed() + TIMEOUT_SLACK >= timeout);
+}
+
+#[test]
+fn zero_timeout_polls() {
+    let event = Event::new(false, true).unwrap();
+    for _ in 0..1000 {
+        assert!(event.wait_for(Duration::from_secs(0)).timed_out());
+    };
+    event.notify();
+    assert!(!event.wait_for(Duration::from_secs(0)).timed_out());
+    assert!(event.wait_for(Duration::from_secs(0)).timed_out());
+}
+
+#[test]
+fn wait_for_any_returns_signaled_index() {
+    let event_vec = new_events(4, false);
+    event_vec[2].notify();
+    assert_eq!(wait_for_any(&event_vec), 2);
+    let timeout = Duration::from_millis(10);
+    assert_eq!(wait_for_any_with(&event_vec, timeout), Ok(2));
+    event_vec[2].unnotify();
+    assert!(wait_for_any_with(&event_vec, timeout).is_err());
+
+    crossbeam::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            event_vec[3].notify();
+        });
+        assert_eq!(wait_for_any(&event_vec), 3);
+    });
+}
+
+#[test]
+fn wait_for_any_consumes_auto_reset_winner_only() {
+    let event_vec = new_events(3, true);
+    event_vec[1].notify();
+    event_vec[2].notify();
+    let winner = wait_for_any(&event_vec);
+    assert!(winner == 1 || winner == 2);
+    assert!(!event_vec[winner].try_wait());
+    assert!(event_vec[3 - winner].try_wait());
+
+    crossbeam::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            event_vec[0].notify();
+        });
+        assert_eq!(wait_for_any(&event_vec), 0);
+    });
+    assert!(!event_vec[0].try_wait());
+}
+
+#[test]
+fn wait_for_all_consumes_auto_reset_signals() {
+    let event_vec = new_events(3, true);
+    for event in &event_vec {
+        event.notify();
+    };
+    wait_for_all(&event_vec);
+    assert!(event_vec.iter().all(|event| !event.try_wait()));
+
+    event_vec[0].notify();
+    let timeout = Duration::from_millis(20);
+    assert!(wait_for_all_with(&event_vec, timeout).timed_out());
+}
+
+#[test]
+fn empty_slice_is_rejected() {
+    let no_events: Vec<Arc<Event>> = vec![];
+    assert!(panics(|| wait_for_any(&no_events)));
+    assert!(panics(|| wait_for_all(&no_events)));
+    assert_eq!(try_wait_for_any(&no_events), Err(WaitError::EmptySlice));
+    assert_eq!(try_wait_for_all(&no_events), Err(WaitError::EmptySlice));
+}
+
+#[test]
+fn past_deadline_is_rejected() {
+    let event = Arc::new(Event::new(true, false).unwrap());
+    let event_vec = vec![event.clone()];
+    let past = Instant::now() - Duration::from_millis(100);
+    assert!(panics(|| event.wait_until(past)));
+    assert!(panics(|| wait_for_any_until(&event_vec, past)));
+    assert!(panics(|| wait_for_all_until(&event_vec, past)));
+    assert_eq!(event.try_wait_until(past), Err(WaitError::NegativeTimeout));
+}
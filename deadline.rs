@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use super::{Event, WaitOutcome, WaitTimeoutResult};
+use super::{wait_for_any_with, wait_for_all_with};
+use super::log::catch_callback_panic;
+use super::stats::elapsed_nanos;
+
+// The most a `wait_drawing_budget` call claims from the shared budget at a
+// time, so that concurrent waiters draw from it side by side.
+const BUDGET_SLICE_NANOS: u64 = 10_000_000;
+
+/// An overall time budget shared by a sequence of waits.
+///
+/// Each `_within` wait reads the time remaining on entry, so waits chained
+/// under one `Deadline` never exceed it in total.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    pub fn after(budget: Duration) -> Self {
+        Deadline { at: Instant::now() + budget }
+    }
+
+    pub fn at(at: Instant) -> Self {
+        Deadline { at: at }
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.at
+    }
+
+    /// The time left before the deadline, zero once it has passed.
+    pub fn remaining(&self) -> Duration {
+        let now = Instant::now();
+        if self.at > now {
+            self.at - now
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+}
+
+/// Returned by `Event::wait_for_or_abort` when the hard ceiling passes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HardTimeout;
+
+impl Event {
+    /// Waits for the event for at most the time left on `deadline`. Times out
+    /// immediately, without checking the event, if it has already expired.
+    pub fn wait_within(&self, deadline: &Deadline) -> WaitTimeoutResult {
+        if deadline.is_expired() {
+            return expired();
+        };
+        self.wait_for(deadline.remaining())
+    }
+
+    /// Waits for the event for at most `hard`, calling `on_soft` once if it
+    /// is still unsignaled after `soft`, e.g. to log a warning or escalate.
+    /// `on_soft` is never called if `soft` is not shorter than `hard`.
+    pub fn wait_for_or_abort<F: FnOnce()>(
+        &self,
+        soft: Duration,
+        hard: Duration,
+        on_soft: F
+    ) -> Result<(), HardTimeout> {
+        let deadline = Deadline::after(hard);
+        if soft < hard {
+            if !self.wait_for(soft).timed_out() {
+                return Ok(());
+            };
+            on_soft();
+        };
+        if self.wait_for(deadline.remaining()).timed_out() {
+            Err(HardTimeout)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Waits for the event for at most the nanoseconds left in `budget`, a
+    /// time pool shared by a group of threads, deducting the time waited.
+    ///
+    /// The budget is claimed in short slices and the unused part of a slice
+    /// is handed back on wake, so the total time waited by all threads
+    /// drawing from one budget stays within it. Times out immediately once
+    /// the budget is exhausted.
+    pub fn wait_drawing_budget(&self, budget: &AtomicU64) ->
+        WaitTimeoutResult
+    {
+        loop {
+            let claimed = budget.fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |left| Some(left.saturating_sub(BUDGET_SLICE_NANOS))
+            );
+            let claim = match claimed {
+                Ok(left) | Err(left) => left.min(BUDGET_SLICE_NANOS),
+            };
+            if claim == 0 {
+                return expired();
+            };
+            let start = Instant::now();
+            let result = self.wait_for(Duration::from_nanos(claim));
+            let waited = elapsed_nanos(start);
+            if waited < claim {
+                budget.fetch_add(claim - waited, Ordering::AcqRel);
+            };
+            if !result.timed_out() {
+                return result;
+            };
+        };
+    }
+
+    /// Waits for the event until a deadline that `refresh` may push out.
+    ///
+    /// Starts with `initial`. Whenever the current deadline passes, `refresh`
+    /// is asked for a new one; the wait times out only when it returns `None`
+    /// or a deadline that has passed too. Suits idle timeouts, where each
+    /// sign of activity extends the deadline.
+    ///
+    /// If `refresh` panics, the wait returns `CallbackPanicked` instead of
+    /// unwinding.
+    pub fn wait_with_sliding_deadline<F>(
+        &self,
+        initial: Instant,
+        mut refresh: F
+    ) -> WaitOutcome
+        where F: FnMut() -> Option<Instant>
+    {
+        let mut deadline = initial;
+        loop {
+            let timed_out = self.try_wait_until(deadline)
+                .map_or(true, |result| result.timed_out());
+            if !timed_out {
+                return WaitOutcome::Signaled;
+            };
+            match catch_callback_panic("refresh", &mut refresh) {
+                None => return WaitOutcome::CallbackPanicked,
+                Some(Some(next)) if next > Instant::now() => deadline = next,
+                Some(_) => return WaitOutcome::TimedOut,
+            };
+        };
+    }
+}
+
+/// Like `wait_for_any_with`, bounded by the time left on `deadline`.
+pub fn wait_for_any_within(slice: &[Arc<Event>], deadline: &Deadline) ->
+    Result<usize, WaitTimeoutResult>
+{
+    if deadline.is_expired() {
+        return Err(expired());
+    };
+    wait_for_any_with(slice, deadline.remaining())
+}
+
+/// Like `wait_for_all_with`, bounded by the time left on `deadline`.
+pub fn wait_for_all_within(slice: &[Arc<Event>], deadline: &Deadline) ->
+    WaitTimeoutResult
+{
+    if deadline.is_expired() {
+        return expired();
+    };
+    wait_for_all_with(slice, deadline.remaining())
+}
+
+fn expired() -> WaitTimeoutResult {
+    WaitTimeoutResult::new(true)
+}
@@ -0,0 +1,109 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+use super::Event;
+
+/// Identifies an event in the wait-for graph: its address, which is stable
+/// for as long as the event lives.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EventId(pub usize);
+
+#[derive(Default)]
+struct Graph {
+    // Every thread that has ever notified an event.
+    notifiers: HashMap<EventId, HashSet<ThreadId>>,
+    // The event each thread is currently blocked on.
+    blocked: HashMap<ThreadId, EventId>,
+}
+
+static GRAPH: Mutex<Option<Graph>> = Mutex::new(None);
+
+fn with_graph<F, T>(f: F) -> T
+    where F: FnOnce(&mut Graph) -> T
+{
+    f(GRAPH.lock().unwrap().get_or_insert_with(Graph::default))
+}
+
+impl Event {
+    pub fn id(&self) -> EventId {
+        EventId(self as *const Event as usize)
+    }
+}
+
+pub fn record_notify(event: &Event) {
+    let id = event.id();
+    with_graph(|graph| {
+        graph.notifiers.entry(id).or_default().insert(thread::current().id());
+    });
+}
+
+pub fn forget(event: &Event) {
+    let id = event.id();
+    with_graph(|graph| graph.notifiers.remove(&id));
+}
+
+/// Marks the current thread as blocked on an event until dropped.
+pub struct Blocked;
+
+pub fn blocked_on(event: &Event) -> Blocked {
+    let id = event.id();
+    with_graph(|graph| graph.blocked.insert(thread::current().id(), id));
+    Blocked
+}
+
+impl Drop for Blocked {
+    fn drop(&mut self) {
+        with_graph(|graph| graph.blocked.remove(&thread::current().id()));
+    }
+}
+
+/// Returns every cycle in the current wait-for graph, each starting with
+/// its smallest id.
+///
+/// Event `a` waits for event `b` while a thread that has notified `a`
+/// before is blocked on `b`, so in a cycle each event can only be signaled
+/// once the next one is. That is a likely deadlock, not a certain one:
+/// another thread may still notify any of the events. Only the
+/// single-event waits `wait`, `wait_for` and `wait_until` are recorded.
+pub fn detect_cycles() -> Vec<Vec<EventId>> {
+    with_graph(|graph| {
+        let mut edges: HashMap<EventId, Vec<EventId>> = HashMap::new();
+        for (&id, threads) in &graph.notifiers {
+            for thread in threads {
+                if let Some(&next) = graph.blocked.get(thread) {
+                    edges.entry(id).or_default().push(next);
+                };
+            };
+        };
+        let mut starts = edges.keys().cloned().collect::<Vec<_>>();
+        starts.sort();
+        let mut cycles = Vec::new();
+        for start in starts {
+            let mut path = vec![start];
+            find_cycles(&edges, &mut path, &mut cycles);
+        };
+        cycles
+    })
+}
+
+// Extends `path` along every edge, recording the paths that return to its
+// first node. Only nodes greater than the first are visited, so each cycle
+// is found once, from its smallest node.
+fn find_cycles(
+    edges: &HashMap<EventId, Vec<EventId>>,
+    path: &mut Vec<EventId>,
+    cycles: &mut Vec<Vec<EventId>>
+) {
+    let start = path[0];
+    let last = path[path.len() - 1];
+    for &next in edges.get(&last).map_or(&[][..], |next| &next[..]) {
+        if next == start {
+            cycles.push(path.clone());
+        } else if next > start && !path.contains(&next) {
+            path.push(next);
+            find_cycles(edges, path, cycles);
+            path.pop();
+        };
+    };
+}
@@ -0,0 +1,154 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::Event;
+use super::threads::spawn_named;
+
+/// Signals events after a delay from a single shared background thread.
+///
+/// Scheduled notifications are kept in a heap ordered by deadline; the thread
+/// sleeps until the earliest one is due. Dropping the notifier stops the
+/// thread, discarding any notifications that have not fired yet.
+pub struct DelayedNotifier {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+struct State {
+    heap: BinaryHeap<Scheduled>,
+    next_seq: u64,
+    shutdown: bool,
+}
+
+struct Scheduled {
+    deadline: Instant,
+    seq: u64,
+    event: Arc<Event>,
+    action: Action,
+}
+
+enum Action {
+    Notify,
+    // The second step of `Event::notify_escalating`.
+    #[cfg(not(windows))]
+    Escalate,
+}
+
+// The notifier behind `Event::notify_escalating`, started on first use.
+#[cfg(not(windows))]
+static ESCALATIONS: Mutex<Option<DelayedNotifier>> = Mutex::new(None);
+
+#[cfg(not(windows))]
+pub(crate) fn escalate_after(event: Arc<Event>, delay: Duration) {
+    ESCALATIONS.lock().unwrap()
+        .get_or_insert_with(DelayedNotifier::new)
+        .schedule(event, delay, Action::Escalate);
+}
+
+// `BinaryHeap` is a max-heap, so the ordering is reversed to pop the earliest
+// deadline first. `seq` keeps events scheduled for the same instant in FIFO
+// order.
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.deadline.cmp(&self.deadline).then(other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl DelayedNotifier {
+    pub fn new() -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                heap: BinaryHeap::new(),
+                next_seq: 0,
+                shutdown: false,
+            }),
+            condvar: Condvar::new(),
+        });
+        let thread_shared = shared.clone();
+        let thread =
+            spawn_named("delayed-notifier", move || run(&thread_shared));
+        DelayedNotifier {
+            shared: shared,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn notify_after(&self, event: Arc<Event>, delay: Duration) {
+        self.schedule(event, delay, Action::Notify);
+    }
+
+    fn schedule(&self, event: Arc<Event>, delay: Duration, action: Action) {
+        let mut state = self.shared.state.lock().unwrap();
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.heap.push(Scheduled {
+            deadline: Instant::now() + delay,
+            seq: seq,
+            event: event,
+            action: action,
+        });
+        self.shared.condvar.notify_one();
+    }
+}
+
+impl Default for DelayedNotifier {
+    fn default() -> Self {
+        DelayedNotifier::new()
+    }
+}
+
+fn run(shared: &Shared) {
+    let mut state = shared.state.lock().unwrap();
+    while !state.shutdown {
+        let now = Instant::now();
+        let deadline = state.heap.peek().map(|scheduled| scheduled.deadline);
+        state = match deadline {
+            None => shared.condvar.wait(state).unwrap(),
+            Some(deadline) if deadline <= now => {
+                let scheduled = state.heap.pop().unwrap();
+                drop(state);
+                match scheduled.action {
+                    Action::Notify => scheduled.event.notify(),
+                    #[cfg(not(windows))]
+                    Action::Escalate => scheduled.event.escalate(),
+                };
+                shared.state.lock().unwrap()
+            },
+            Some(deadline) => {
+                shared.condvar.wait_timeout(state, deadline - now).unwrap().0
+            },
+        };
+    };
+}
+
+impl Drop for DelayedNotifier {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.condvar.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        };
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+
+use super::{Event, wait_for_any};
+use super::log::catch_callback_panic;
+use super::threads::spawn_named;
+
+/// Stops the forwarder started by `Event::forward_to_sender` when dropped.
+pub struct Forwarder {
+    stop: Arc<Event>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Event {
+    /// Spawns a thread that sends `make_msg()` on `tx` each time the event
+    /// is signaled, so a consumer blocked in `recv` also wakes on the event.
+    ///
+    /// The event must be auto-reset, so that each signal is forwarded once.
+    /// Forwarding stops when the returned `Forwarder` is dropped or the
+    /// receiver hangs up. A signal for which `make_msg` panics is logged
+    /// through the `set_log_hook` hook and dropped.
+    pub fn forward_to_sender<T, F>(self: Arc<Self>, tx: Sender<T>, make_msg: F)
+        -> Forwarder
+        where T: Send + 'static,
+              F: Fn() -> T + Send + 'static
+    {
+        assert!(
+            self.capture_state().auto_reset,
+            "forward_to_sender needs an auto-reset event."
+        );
+        let stop = Arc::new(Event::new(false, false).unwrap());
+        let events = vec![stop.clone(), self];
+        let thread = spawn_named("forwarder", move || {
+            while wait_for_any(&events) == 1 {
+                let msg = catch_callback_panic("forward_to_sender", &make_msg);
+                if let Some(msg) = msg {
+                    if tx.send(msg).is_err() {
+                        break;
+                    };
+                };
+            };
+        });
+        Forwarder {
+            stop: stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Forwarder {
+    fn drop(&mut self) {
+        self.stop.notify();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        };
+    }
+}
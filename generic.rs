@@ -1,146 +1,1851 @@
 extern crate parking_lot;
 extern crate ordermap;
-extern crate chrono;
+#[cfg(any(target_os = "linux", feature = "async-std"))]
+extern crate libc;
+#[cfg(feature = "async-std")]
+extern crate async_std;
+#[cfg(all(feature = "async-std", target_os = "linux"))]
+extern crate async_io;
+#[cfg(feature = "mio")]
+extern crate mio;
+#[cfg(feature = "rand")]
+extern crate rand;
 
 use std::usize::MAX as USIZE_MAX;
+use std::any::Any;
+use std::borrow::Borrow;
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::panic::Location;
 use std::result::Result;
-use std::mem::transmute;
-use std::sync::Arc;
+use std::mem::{self, transmute};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread::{self, Thread};
 use std::time::{Duration, Instant};
 
-use self::parking_lot::{Condvar, Mutex, RwLock};
+#[cfg(all(feature = "async-std", target_os = "linux"))]
+use std::fs::File;
+#[cfg(feature = "async-std")]
+use std::future::Future;
+#[cfg(feature = "async-std")]
+use std::io::Read;
+#[cfg(all(feature = "async-std", target_os = "linux"))]
+use std::os::unix::io::FromRawFd;
+#[cfg(feature = "async-std")]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(all(feature = "async-std", not(target_os = "linux")))]
+use std::os::unix::net::UnixStream;
+#[cfg(feature = "async-std")]
+use std::pin::Pin;
+#[cfg(feature = "async-std")]
+use std::task::{Context, Poll};
+
+#[cfg(all(feature = "async-std", target_os = "linux"))]
+use self::async_io::Async;
+#[cfg(all(feature = "async-std", not(target_os = "linux")))]
+use self::async_std::task::{self, JoinHandle};
+
+use self::parking_lot::{Condvar, Mutex, MutexGuard, RwLock};
 
 use self::ordermap::OrderMap;
 
-use self::chrono::Duration as ChDuration;
+#[cfg(feature = "deadlock-detection")]
+use super::deadlock;
+#[cfg(feature = "registry")]
+use super::registry::RegistryEntry;
+#[cfg(feature = "trace")]
+use super::trace::{self, TraceOp};
+use super::timeout::WaitMs;
+use super::stats::{EventStats, NotifyIntervals, StatsCounters};
+use super::stats::{elapsed_nanos, next_notify_seq};
+use super::hierarchy::Children;
+use super::log::{catch_callback_panic, log};
+use super::parker::{CondvarParker, ParkResult, Parker};
+use super::parker::SharedCondvarParker;
+use super::delayed;
+
+pub struct Event {
+    // Shared with external code for events built with `from_parts`.
+    mutex: Arc<Mutex<bool>>,
+    condvar: Arc<Condvar>,
+    auto_reset: bool,
+    // Allocated on the first multi-wait registration; most events are only
+    // ever waited on alone.
+    map: RwLock<Option<Box<WaiterMap>>>,
+    // Only read or written while `mutex` is held.
+    poisoned: AtomicBool,
+    generation: AtomicU64,
+    coalesced_generation: AtomicU64,
+    reset_generation: AtomicU64,
+    // Paired with `mutex`, for `wait_for_reset_edge` alone, so that resets
+    // don't wake the other waiters.
+    reset_condvar: Condvar,
+    // Only written while `mutex` is held.
+    notify_seq: AtomicU64,
+    holders: AtomicUsize,
+    // Threads blocked in `wait` or `wait_until_impl`.
+    waiters: AtomicUsize,
+    parker: Arc<dyn Parker>,
+    // Waiters blocked in `parker`. Only written while `mutex` is held.
+    parked: AtomicUsize,
+    // Unparks given to `parker` that no park has taken yet. Only written
+    // while `mutex` is held.
+    unparked: AtomicUsize,
+    redundant_notifies: AtomicU64,
+    stats: Option<Arc<StatsCounters>>,
+    max_wait_nanos: Option<AtomicU64>,
+    // Only locked while `mutex` is held.
+    waiter_location: Option<Mutex<Option<&'static Location<'static>>>>,
+    notify_intervals: Option<NotifyIntervals>,
+    children: Children,
+    // Allocated on first use, like `map`. Only locked while `mutex` is held.
+    extras: Mutex<Option<Box<Extras>>>,
+    // Lock-free view of the state for `try_wait_with_ordering`. May lag
+    // behind a consumed auto-reset signal, never behind a notify. Notifies
+    // set it with a read-modify-write, so that they continue the release
+    // sequence of a `Release` store from `notify_with_ordering`.
+    signaled_mirror: AtomicBool,
+    // Allocated on the first `wait_adaptive` or `set_adaptive_default`.
+    latencies: Mutex<Option<Box<Latencies>>>,
+    last_rate_limited_return: Mutex<Option<Instant>>,
+    #[cfg(feature = "registry")]
+    registry_entry: Arc<RegistryEntry>,
+}
+
+// The state of the waits and registrations that most events never see.
+#[derive(Default)]
+struct Extras {
+    filtered_waiters: Vec<(u64, Arc<AtomicBool>)>,
+    external_condvars: Vec<Arc<ExternalCondvar>>,
+    unpark_threads: Vec<Thread>,
+    once_callbacks: Vec<Box<dyn FnOnce() + Send>>,
+    reply: Option<Box<dyn Any + Send>>,
+    // The fds that wake pending `wait_async_std` futures when written to.
+    #[cfg(feature = "async-std")]
+    async_fds: Vec<RawFd>,
+    #[cfg(feature = "mio")]
+    mio_wakers: Vec<Arc<mio::Waker>>,
+}
+
+impl Extras {
+    // The part of `signal_locked` that wakes the registered waiters.
+    fn signal(&self) -> Vec<Arc<ExternalCondvar>> {
+        for external in self.external_condvars.iter() {
+            external.notifying.fetch_add(1, Ordering::Relaxed);
+        };
+        for thread in self.unpark_threads.iter() {
+            thread.unpark();
+        };
+        #[cfg(feature = "async-std")]
+        for &fd in self.async_fds.iter() {
+            wake_async_fd(fd);
+        };
+        #[cfg(feature = "mio")]
+        for waker in self.mio_wakers.iter() {
+            let _ = waker.wake();
+        };
+        self.external_condvars.clone()
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct MutexKey {
+    mutex: * const Mutex<usize>,
+}
+
+unsafe impl Send for MutexKey {}
+unsafe impl Sync for MutexKey {}
+
+struct CondvarWithId {
+    condvar: * const Condvar,
+    id: usize,
+    kind: WaitFor,
+}
+
+unsafe impl Send for CondvarWithId {}
+unsafe impl Sync for CondvarWithId {}
+
+type WaiterMap = OrderMap<MutexKey, CondvarWithId>;
+
+enum WaitFor {
+    Any,
+    All,
+}
+
+// Lowers the nice value of the current thread by one until dropped, if
+// permitted.
+#[cfg(target_os = "linux")]
+struct PriorityBoost {
+    tid: libc::id_t,
+    previous: Option<libc::c_int>,
+}
+
+#[cfg(target_os = "linux")]
+impl PriorityBoost {
+    fn raise() -> Self {
+        let tid = unsafe { libc::syscall(libc::SYS_gettid) } as libc::id_t;
+        let previous = current_nice(tid).and_then(|nice| {
+            let result =
+                unsafe { libc::setpriority(libc::PRIO_PROCESS, tid, nice - 1) };
+            if result == 0 { Some(nice) } else { None }
+        });
+        PriorityBoost {
+            tid: tid,
+            previous: previous,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for PriorityBoost {
+    fn drop(&mut self) {
+        if let Some(nice) = self.previous {
+            unsafe { libc::setpriority(libc::PRIO_PROCESS, self.tid, nice) };
+        };
+    }
+}
+
+// `getpriority` may legitimately return -1, so failure is told apart via
+// `errno`.
+#[cfg(target_os = "linux")]
+fn current_nice(tid: libc::id_t) -> Option<libc::c_int> {
+    unsafe {
+        *libc::__errno_location() = 0;
+        let nice = libc::getpriority(libc::PRIO_PROCESS, tid);
+        if nice == -1 && *libc::__errno_location() != 0 {
+            None
+        } else {
+            Some(nice)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+struct PriorityBoost;
+
+#[cfg(not(target_os = "linux"))]
+impl PriorityBoost {
+    fn raise() -> Self {
+        PriorityBoost
+    }
+}
+
+// A caller's condvar that `notify` also wakes, registered for the duration
+// of a `wait_or_condvar`. It is woken once the event's lock is released,
+// with the caller's mutex held so that the waiter can't be between checking
+// the event and parking. `notifying` counts the notifies about to do that;
+// the waiter doesn't return while any are, keeping the pointers valid.
+struct ExternalCondvar {
+    condvar: * const Condvar,
+    mutex: * const (),
+    wake: unsafe fn(&ExternalCondvar),
+    notifying: AtomicUsize,
+}
+
+unsafe impl Send for ExternalCondvar {}
+unsafe impl Sync for ExternalCondvar {}
+
+// Locking the caller's mutex waits for a `wait_or_condvar` that registered
+// `external` to park, so the wakeup can't be lost. The count drops before
+// the unlock, so the waiter sees it once it wakes.
+unsafe fn wake_external_condvar<T>(external: &ExternalCondvar) {
+    let mutex = &*(external.mutex as * const Mutex<T>);
+    let _guard = mutex.lock();
+    (*external.condvar).notify_all();
+    external.notifying.fetch_sub(1, Ordering::Relaxed);
+}
+
+fn wake_external_condvars(externals: Vec<Arc<ExternalCondvar>>) {
+    for external in externals {
+        unsafe { (external.wake)(&external) };
+    };
+}
+
+/// Which side ended an `Event::wait_or_condvar`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WhichWoke {
+    Event,
+    Condvar,
+}
+
+/// Whether an `Event::wait_consuming` call took the signal that woke it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConsumeResult {
+    /// This thread reset the event.
+    Consumed,
+    /// The event was left signaled, or another thread reset it first.
+    Observed,
+}
+
+// How many signaled `wait_adaptive` calls the adaptive timeout is derived
+// from, and how many it needs before it stops using the cold-start default.
+const LATENCY_SAMPLES: usize = 16;
+const MIN_LATENCY_SAMPLES: usize = 4;
+
+// Ring buffer of recent `wait_adaptive` latencies, in nanoseconds.
+struct Latencies {
+    samples: [u64; LATENCY_SAMPLES],
+    len: usize,
+    next: usize,
+    cold_start: Duration,
+}
+
+impl Default for Latencies {
+    fn default() -> Self {
+        Latencies {
+            samples: [0; LATENCY_SAMPLES],
+            len: 0,
+            next: 0,
+            cold_start: Duration::from_secs(1),
+        }
+    }
+}
+
+impl Latencies {
+    fn record(&mut self, nanos: u64) {
+        self.samples[self.next] = nanos;
+        self.next = (self.next + 1) % LATENCY_SAMPLES;
+        self.len = LATENCY_SAMPLES.min(self.len + 1);
+    }
+
+    fn timeout(&self, multiplier: f64) -> Duration {
+        if self.len < MIN_LATENCY_SAMPLES {
+            return self.cold_start;
+        };
+        let mut samples = self.samples;
+        let samples = &mut samples[..self.len];
+        samples.sort_unstable();
+        Duration::from_nanos((samples[self.len / 2] as f64 * multiplier) as u64)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WaitTimeoutResult {
+    timed_out: bool,
+}
+
+impl WaitTimeoutResult {
+    pub(crate) fn new(timed_out: bool) -> Self {
+        WaitTimeoutResult { timed_out: timed_out }
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimedOut;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Poisoned;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TooManySpurious;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WaitOutcome {
+    Signaled,
+    TimedOut,
+    Cancelled,
+    ShutdownRequested,
+    /// A callback run by the wait panicked; the event is left usable.
+    CallbackPanicked,
+}
+
+/// What happened during an `Event::wait_diagnostic` call.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WaitDiagnostic {
+    /// `Signaled` or `TimedOut`.
+    pub outcome: WaitOutcome,
+    /// Wakeups that found the event unsignaled and went back to waiting.
+    pub spurious_wakeups: u32,
+    pub elapsed: Duration,
+    /// Whether the event was already signaled on entry, so the call never
+    /// blocked.
+    pub fast_path: bool,
+}
+
+/// Invalid input to a wait, returned by the `try_` variants of the waiting
+/// functions. Those never panic on bad input and are the robust choice for
+/// timeouts or event sets that come from outside the program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WaitError {
+    /// More events than the platform can wait on at once (Windows only).
+    TooManyObjects,
+    TimeoutTooLarge,
+    /// The deadline has already passed.
+    NegativeTimeout,
+    EmptySlice,
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            WaitError::TooManyObjects => "Too many events to wait for.",
+            WaitError::TimeoutTooLarge => "Time period too large.",
+            WaitError::NegativeTimeout => "Cannot wait for a previous time.",
+            WaitError::EmptySlice => "Cannot wait for an empty set of events.",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl Error for WaitError {}
+
+fn deadline_after(timeout: Duration) -> Result<Instant, WaitError> {
+    Instant::now().checked_add(timeout).ok_or(WaitError::TimeoutTooLarge)
+}
+
+// The panicking counterpart of `deadline_after`, for the plain waits.
+fn deadline_for(timeout: Duration) -> Instant {
+    deadline_after(timeout).unwrap_or_else(|_e| {
+        panic!("Time period too large.");
+    })
+}
+
+fn check_deadline(timeout: Instant) -> Result<(), WaitError> {
+    if timeout < Instant::now() {
+        Err(WaitError::NegativeTimeout)
+    } else {
+        Ok(())
+    }
+}
+
+fn check_slice(slice: &[Arc<Event>]) -> Result<(), WaitError> {
+    if slice.is_empty() {
+        Err(WaitError::EmptySlice)
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Mark {
+    generation: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EventState {
+    pub signaled: bool,
+    pub auto_reset: bool,
+}
+
+#[derive(Debug)]
+pub enum EventError {
+    Os(io::Error),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EventError::Os(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+static GLOBAL_SHUTDOWN_EVENT: StdMutex<Option<Arc<Event>>> =
+    StdMutex::new(None);
+
+/// Registers the event that `Event::wait_respecting_shutdown` treats as the
+/// process-wide shutdown signal, replacing any previous one.
+pub fn set_global_shutdown_event(event: Arc<Event>) {
+    *GLOBAL_SHUTDOWN_EVENT.lock().unwrap() = Some(event);
+}
+
+impl From<parking_lot::WaitTimeoutResult> for WaitTimeoutResult {
+    fn from(wtr: parking_lot::WaitTimeoutResult) -> Self {
+        WaitTimeoutResult { timed_out: wtr.timed_out() }
+    }
+}
+
+impl Event {
+    pub fn new(initial_signaled: bool, auto_reset: bool) -> Result<Self, ()> {
+        let parker = Arc::new(CondvarParker::new());
+        Ok(Event::with_parker(initial_signaled, auto_reset, parker))
+    }
+
+    /// Builds an event whose single-event waits, from `wait` to
+    /// `wait_consuming`, `wait_since` or `wait_and_hold`, block through
+    /// `parker` instead of a `CondvarParker`; see `Parker`. Multi-event
+    /// waits and `wait_for_reset_edge` block on their own condvars and are
+    /// unaffected.
+    pub fn with_parker(
+        initial_signaled: bool,
+        auto_reset: bool,
+        parker: Arc<dyn Parker>
+    ) -> Self {
+        Event::with_parts(
+            Arc::new(Mutex::new(initial_signaled)),
+            Arc::new(Condvar::new()),
+            auto_reset,
+            parker
+        )
+    }
+
+    /// Builds an event on top of a mutex and condvar that external code
+    /// already uses, so the crate's wait API can be adopted incrementally.
+    ///
+    /// The event and the external code share both primitives: the `bool` is
+    /// the signaled state, and whoever sets it to `true` must `notify_all`
+    /// the condvar, as `notify` does. Waiters on either side are then woken
+    /// by either side. Signals set or cleared externally bypass the event's
+    /// bookkeeping, so they do not reach multi-event waits, `notify_once`
+    /// callbacks, stats or `try_wait_with_ordering`.
+    pub fn from_parts(
+        mutex: Arc<Mutex<bool>>,
+        condvar: Arc<Condvar>,
+        auto_reset: bool
+    ) -> Self {
+        let parker = Arc::new(
+            SharedCondvarParker::new(mutex.clone(), condvar.clone())
+        );
+        Event::with_parts(mutex, condvar, auto_reset, parker)
+    }
+
+    fn with_parts(
+        mutex: Arc<Mutex<bool>>,
+        condvar: Arc<Condvar>,
+        auto_reset: bool,
+        parker: Arc<dyn Parker>
+    ) -> Self {
+        let initial_signaled = *mutex.lock();
+        #[cfg(feature = "registry")]
+        let probed = Arc::downgrade(&mutex);
+        Event {
+            mutex: mutex,
+            condvar: condvar,
+            auto_reset: auto_reset,
+            map: RwLock::new(None),
+            poisoned: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            coalesced_generation: AtomicU64::new(0),
+            reset_generation: AtomicU64::new(0),
+            reset_condvar: Condvar::new(),
+            notify_seq: AtomicU64::new(0),
+            holders: AtomicUsize::new(0),
+            waiters: AtomicUsize::new(0),
+            parker: parker,
+            parked: AtomicUsize::new(0),
+            unparked: AtomicUsize::new(0),
+            redundant_notifies: AtomicU64::new(0),
+            stats: None,
+            max_wait_nanos: None,
+            waiter_location: None,
+            notify_intervals: None,
+            children: Children::default(),
+            extras: Mutex::new(None),
+            signaled_mirror: AtomicBool::new(initial_signaled),
+            latencies: Mutex::new(None),
+            last_rate_limited_return: Mutex::new(None),
+            #[cfg(feature = "registry")]
+            registry_entry: RegistryEntry::register(
+                auto_reset,
+                Box::new(move || {
+                    probed.upgrade().is_some_and(|mutex| *mutex.lock())
+                })
+            ),
+        }
+    }
+
+    pub(crate) fn enable_stats(&mut self) {
+        self.stats = Some(Arc::new(StatsCounters::default()));
+    }
+
+    pub(crate) fn reserve_waiters(&mut self, capacity: usize) {
+        // An explicit capacity is allocated upfront. `OrderMap::reserve` only
+        // grows by one step, so the map is built with it instead.
+        *self.map.get_mut() = Some(Box::new(OrderMap::with_capacity(capacity)));
+    }
+
+    /// How many multi-wait registrations the event can hold without
+    /// reallocating.
+    pub fn waiter_capacity(&self) -> usize {
+        self.map.read().as_ref().map_or(0, |map| map.capacity())
+    }
+
+    fn add_waiter(&self) {
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "registry")]
+        self.registry_entry.add_waiter();
+    }
+
+    fn remove_waiter(&self) {
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        #[cfg(feature = "registry")]
+        self.registry_entry.remove_waiter();
+    }
+
+    #[cfg(feature = "registry")]
+    pub(crate) fn registry_entry(&self) -> &RegistryEntry {
+        &self.registry_entry
+    }
+
+    /// Whether any thread is currently blocked in one of the event's waits,
+    /// alone or as part of a multi-event wait. Pending `wait_async_std`
+    /// futures are not counted. Cheaper than counting the waiters, and just
+    /// as stale by the time it returns.
+    pub fn has_waiters(&self) -> bool {
+        self.waiters.load(Ordering::Relaxed) > 0 ||
+            self.map.read().as_ref().is_some_and(|map| !map.is_empty())
+    }
+
+    pub(crate) fn track_max_wait(&mut self) {
+        self.max_wait_nanos = Some(AtomicU64::new(0));
+    }
+
+    pub(crate) fn track_waiter_locations(&mut self) {
+        self.waiter_location = Some(Mutex::new(None));
+    }
+
+    /// Where the last plain wait on the event was called from, on events
+    /// built with `EventBuilder::track_waiter_locations(true)`. Covers `wait`,
+    /// `wait_for`, `wait_until` and their variants; `None` until the first
+    /// of them.
+    pub fn last_waiter_location(&self) -> Option<&'static Location<'static>> {
+        let _guard = self.mutex.lock();
+        *self.waiter_location.as_ref()?.lock()
+    }
+
+    // Called with `mutex` held.
+    #[track_caller]
+    fn record_location(&self) {
+        if let Some(ref location) = self.waiter_location {
+            *location.lock() = Some(Location::caller());
+        };
+    }
+
+    pub(crate) fn children(&self) -> &Children {
+        &self.children
+    }
+
+    pub(crate) fn track_notify_intervals(&mut self) {
+        self.notify_intervals = Some(NotifyIntervals::default());
+    }
+
+    /// A guess at how long until the next `notify`, on events built with
+    /// `EventBuilder::track_notify_intervals(true)`: the moving average of
+    /// the time between past notifies, minus the time since the last one,
+    /// and zero once that average has passed.
+    ///
+    /// Only a heuristic for ordering waits, e.g. across events that fire at
+    /// a steady rate. `None` until a few notifies have been seen, and always
+    /// for any other event.
+    pub fn estimated_time_to_next(&self) -> Option<Duration> {
+        self.notify_intervals.as_ref()?.estimated_time_to_next()
+    }
+
+    /// The longest any single `wait`, `wait_for` or `wait_until` call has
+    /// blocked, on events built with `EventBuilder::track_max_wait(true)`.
+    /// Zero for any other event.
+    pub fn max_wait_duration(&self) -> Duration {
+        match self.max_wait_nanos {
+            Some(ref max) => Duration::from_nanos(max.load(Ordering::Relaxed)),
+            None => Duration::from_secs(0),
+        }
+    }
+
+    pub fn reset_max_wait(&self) {
+        if let Some(ref max) = self.max_wait_nanos {
+            max.store(0, Ordering::Relaxed);
+        };
+    }
+
+    /// Returns the aggregate counters of the event; see `EventStats`.
+    pub fn stats(&self) -> EventStats {
+        EventStats::new(self.stats.clone().unwrap_or_default())
+    }
+
+    /// Rebuilds an event from a state captured with `capture_state`.
+    pub fn from_state(state: EventState) -> Result<Self, EventError> {
+        let parker = Arc::new(CondvarParker::new());
+        Ok(Event::with_parker(state.signaled, state.auto_reset, parker))
+    }
+
+    /// Captures the logical state of the event without consuming a signal.
+    pub fn capture_state(&self) -> EventState {
+        EventState {
+            signaled: *self.mutex.lock(),
+            auto_reset: self.auto_reset,
+        }
+    }
+
+    #[track_caller]
+    pub fn wait(&self) {
+        #[cfg(feature = "deadlock-detection")]
+        let _blocked = deadlock::blocked_on(self);
+        let start = self.wait_started();
+        let mut guard = self.mutex.lock();
+        self.record_location();
+        self.add_waiter();
+        while !*guard {
+            guard = self.park_locked(guard, None).0;
+            if !*guard {
+                self.record_spurious();
+            };
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+        self.record_wait(start, false);
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Wait);
+    }
+
+    #[track_caller]
+    pub fn wait_for(&self, timeout: Duration) -> WaitTimeoutResult {
+        self.wait_until_impl(deadline_for(timeout))
+    }
+
+    /// Waits until the event is notified and reports whether this thread
+    /// consumed the signal.
+    ///
+    /// A notify wakes every waiter of an auto-reset event, and only the
+    /// first to run resets it; where `wait` sends the others back to sleep,
+    /// this returns `Observed` to them. A manual-reset event is never reset
+    /// by a waiter, so it always reports `Observed`.
+    pub fn wait_consuming(&self) -> ConsumeResult {
+        let mut guard = self.mutex.lock();
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.add_waiter();
+        while !*guard &&
+            self.generation.load(Ordering::Relaxed) == generation
+        {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        if *guard && self.auto_reset {
+            *guard = false;
+            ConsumeResult::Consumed
+        } else {
+            ConsumeResult::Observed
+        }
+    }
+
+    /// Waits like `wait` and returns the sequence number of the last
+    /// `notify` of the event. Every notify, on any event, takes the next
+    /// number of a single process-wide counter, so comparing the numbers
+    /// returned for several events tells in which order they were notified.
+    pub fn wait_seq(&self) -> u64 {
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        while !*guard {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+        self.notify_seq.load(Ordering::Relaxed)
+    }
+
+    /// Waits like `wait_for`, with a timeout of `multiplier` times the median
+    /// latency of the last signaled `wait_adaptive` calls, so that a wait
+    /// times out when it runs abnormally long compared to the usual ones.
+    ///
+    /// Until enough calls have been signaled to learn from, the timeout is
+    /// the cold-start default of one second; see `set_adaptive_default`.
+    /// Timed out calls are not learned from.
+    pub fn wait_adaptive(&self, multiplier: f64) -> WaitTimeoutResult {
+        let timeout =
+            self.with_latencies(|latencies| latencies.timeout(multiplier));
+        let start = Instant::now();
+        let result = self.wait_for(timeout);
+        if !result.timed_out() {
+            let nanos = elapsed_nanos(start);
+            self.with_latencies(|latencies| latencies.record(nanos));
+        };
+        result
+    }
+
+    /// Waits like `wait`, then, if the previous `wait_rate_limited` call
+    /// returned less than `min_interval` ago, sleeps the rest of it, so that
+    /// calls return at most once per `min_interval` however fast the event
+    /// is notified. Notifies of an auto-reset event arriving in the meantime
+    /// coalesce into the next return.
+    ///
+    /// The interval is kept per event, across all the threads calling this.
+    #[track_caller]
+    pub fn wait_rate_limited(&self, min_interval: Duration) {
+        self.wait();
+        // Held while sleeping, so that concurrent callers return in turn.
+        let mut last = self.last_rate_limited_return.lock();
+        if let Some(last) = *last {
+            let elapsed = last.elapsed();
+            if elapsed < min_interval {
+                thread::sleep(min_interval - elapsed);
+            };
+        };
+        *last = Some(Instant::now());
+    }
+
+    /// Sets the timeout `wait_adaptive` uses until it has learned the usual
+    /// latency of the event.
+    pub fn set_adaptive_default(&self, cold_start: Duration) {
+        self.with_latencies(|latencies| latencies.cold_start = cold_start);
+    }
+
+    fn with_latencies<F, R>(&self, f: F) -> R
+        where F: FnOnce(&mut Latencies) -> R
+    {
+        f(self.latencies.lock().get_or_insert_with(Box::default))
+    }
+
+    // Called with `mutex` held.
+    fn with_extras<F, R>(&self, f: F) -> R
+        where F: FnOnce(&mut Extras) -> R
+    {
+        f(self.extras.lock().get_or_insert_with(Box::default))
+    }
+
+    /// Waits like `wait_for` for a timeout in whole milliseconds, or like
+    /// `wait` for `WaitMs::INFINITE`.
+    #[track_caller]
+    pub fn wait_for_ms(&self, timeout: WaitMs) -> WaitTimeoutResult {
+        match timeout.to_duration() {
+            Some(timeout) => self.wait_for(timeout),
+            None => {
+                self.wait();
+                WaitTimeoutResult { timed_out: false }
+            },
+        }
+    }
+
+    /// Same as `wait_for`; the condvar timeouts used here are already
+    /// fine-grained. Raises the timer resolution on Windows.
+    #[track_caller]
+    pub fn wait_for_high_res(&self, timeout: Duration) -> WaitTimeoutResult {
+        self.wait_for(timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_until(&self, timeout: Instant) -> WaitTimeoutResult {
+        if timeout < Instant::now() {
+            panic!("Cannot wait for a previous time.");
+        };
+        self.wait_until_impl(timeout)
+    }
+
+    /// Like `wait_for`, but reports an invalid timeout as a `WaitError`
+    /// instead of panicking.
+    #[track_caller]
+    pub fn try_wait_for(&self, timeout: Duration) ->
+        Result<WaitTimeoutResult, WaitError>
+    {
+        let deadline = deadline_after(timeout)?;
+        Ok(self.wait_until_impl(deadline))
+    }
+
+    /// Like `wait_until`, but reports an invalid deadline as a `WaitError`
+    /// instead of panicking.
+    #[track_caller]
+    pub fn try_wait_until(&self, timeout: Instant) ->
+        Result<WaitTimeoutResult, WaitError>
+    {
+        check_deadline(timeout)?;
+        Ok(self.wait_until_impl(timeout))
+    }
+
+    #[track_caller]
+    fn wait_until_impl(&self, timeout: Instant) -> WaitTimeoutResult {
+        #[cfg(feature = "deadlock-detection")]
+        let _blocked = deadlock::blocked_on(self);
+        let start = self.wait_started();
+        let mut ret_value = WaitTimeoutResult { timed_out: false };
+        let mut guard = self.mutex.lock();
+        self.record_location();
+        self.add_waiter();
+        while !*guard && !ret_value.timed_out() {
+            let (relocked, result) = self.park_locked(guard, Some(timeout));
+            guard = relocked;
+            ret_value = WaitTimeoutResult::new(result == ParkResult::TimedOut);
+            if !*guard && !ret_value.timed_out() {
+                self.record_spurious();
+            };
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+        self.record_wait(start, ret_value.timed_out());
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Wait);
+        ret_value
+    }
+
+    /// Waits like `wait_for`, or like `wait` when `timeout` is `None`, and
+    /// reports how the wait went; see `WaitDiagnostic`.
+    pub fn wait_diagnostic(&self, timeout: Option<Duration>) ->
+        WaitDiagnostic
+    {
+        let recorded = self.wait_started();
+        let start = Instant::now();
+        let deadline = timeout.map(deadline_for);
+        let mut spurious_wakeups = 0;
+        let mut timed_out = false;
+        let mut guard = self.mutex.lock();
+        let fast_path = *guard;
+        self.add_waiter();
+        while !*guard && !timed_out {
+            let (relocked, result) = self.park_locked(guard, deadline);
+            guard = relocked;
+            timed_out = result == ParkResult::TimedOut;
+            if !*guard && !timed_out {
+                spurious_wakeups += 1;
+                self.record_spurious();
+            };
+        };
+        self.remove_waiter();
+        let outcome = if *guard {
+            WaitOutcome::Signaled
+        } else {
+            WaitOutcome::TimedOut
+        };
+        if self.auto_reset {
+            *guard = false;
+        };
+        drop(guard);
+        self.record_wait(recorded, outcome == WaitOutcome::TimedOut);
+        WaitDiagnostic {
+            outcome: outcome,
+            spurious_wakeups: spurious_wakeups,
+            elapsed: start.elapsed(),
+            fast_path: fast_path,
+        }
+    }
+
+    // Waits are only timed when stats or max-wait tracking need it.
+    fn wait_started(&self) -> Option<Instant> {
+        if self.stats.is_some() || self.max_wait_nanos.is_some() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    fn record_wait(&self, start: Option<Instant>, timed_out: bool) {
+        let start = match start {
+            Some(start) => start,
+            None => return,
+        };
+        if let Some(ref stats) = self.stats {
+            stats.record_wait(start, timed_out);
+        };
+        if let Some(ref max) = self.max_wait_nanos {
+            max.fetch_max(elapsed_nanos(start), Ordering::Relaxed);
+        };
+    }
+
+    // Parks with `mutex` released, counted in `parked` so that a signal
+    // unparks this thread, and locks it again.
+    fn park_locked<'a>(
+        &'a self,
+        guard: MutexGuard<'a, bool>,
+        deadline: Option<Instant>
+    ) -> (MutexGuard<'a, bool>, ParkResult) {
+        self.parked.fetch_add(1, Ordering::Relaxed);
+        drop(guard);
+        let result = self.parker.park(deadline);
+        let guard = self.mutex.lock();
+        self.parked.fetch_sub(1, Ordering::Relaxed);
+        let unparked = self.unparked.load(Ordering::Relaxed);
+        if result == ParkResult::Unparked && unparked > 0 {
+            self.unparked.store(unparked - 1, Ordering::Relaxed);
+        };
+        (guard, result)
+    }
+
+    // Unparks up to `limit` parked waiters, skipping those an earlier unpark
+    // is already waking so that a permit-based parker doesn't bank permits
+    // for later waits. Returns how many were unparked.
+    fn unpark_locked(&self, limit: usize) -> usize {
+        let parked = self.parked.load(Ordering::Relaxed);
+        let unparked = self.unparked.load(Ordering::Relaxed);
+        let count = cmp::min(parked.saturating_sub(unparked), limit);
+        for _ in 0..count {
+            self.parker.unpark();
+        };
+        self.unparked.store(unparked + count, Ordering::Relaxed);
+        count
+    }
+
+    fn record_spurious(&self) {
+        if let Some(ref stats) = self.stats {
+            stats.record_spurious();
+        };
+    }
+
+    fn register(&self, key: MutexKey, value: CondvarWithId) {
+        let mut map = self.map.write();
+        map.get_or_insert_with(|| Box::new(OrderMap::new())).insert(key, value);
+    }
+
+    fn unregister(&self, key: &MutexKey) {
+        if let Some(ref mut map) = *self.map.write() {
+            map.remove(key);
+        };
+    }
+
+    /// Returns `true` right away if `flag` is set, otherwise waits for the
+    /// event and returns `false`.
+    ///
+    /// For producers that set `flag` with `Release` ordering and then call
+    /// `notify`: the flag is checked with the event's lock held, the same
+    /// lock `notify` takes, so the producer can't slip in between the check
+    /// and the wait.
+    pub fn wait_unless(&self, flag: &AtomicBool) -> bool {
+        let mut guard = self.mutex.lock();
+        if flag.load(Ordering::Acquire) {
+            return true;
+        };
+        self.add_waiter();
+        while !*guard {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+        false
+    }
+
+    /// Waits like `wait`, with the calling thread's scheduling priority
+    /// raised while it is parked so that it runs promptly once woken. The
+    /// previous priority is restored before returning, also on panic.
+    ///
+    /// On Linux the thread's nice value is lowered by one, which needs
+    /// `CAP_SYS_NICE` or a sufficient `RLIMIT_NICE`; without permission the
+    /// wait is not boosted. Elsewhere this is a plain `wait`.
+    ///
+    /// A boosted waiter holding a resource that lower-priority threads need
+    /// can cause priority inversion; keep the boost to waiters that hold no
+    /// locks.
+    pub fn wait_boosted(&self) {
+        let _boost = PriorityBoost::raise();
+        self.wait();
+    }
+
+    pub fn try_wait(&self) -> bool {
+        let mut guard = self.mutex.lock();
+        let signaled = *guard;
+        if signaled && self.auto_reset {
+            *guard = false;
+        };
+        signaled
+    }
+
+    /// Checks the event and, if it isn't signaled, hands control to
+    /// `yield_fn` before checking once more. Returns whether the event was
+    /// signaled (and consumed, if auto-reset).
+    ///
+    /// Meant for cooperative schedulers that must not block the OS thread;
+    /// `yield_fn` switches to another task.
+    pub fn poll_or_yield<F: FnMut()>(&self, mut yield_fn: F) -> bool {
+        if self.try_wait() {
+            return true;
+        };
+        yield_fn();
+        self.try_wait()
+    }
+
+    /// Captures how many times the event has been notified so far, for a
+    /// later `wait_since`.
+    pub fn mark(&self) -> Mark {
+        let _guard = self.mutex.lock();
+        Mark { generation: self.generation.load(Ordering::Relaxed) }
+    }
+
+    /// Blocks until the event has been notified at least once since `mark`
+    /// was taken, returning immediately if that already happened. A signal
+    /// still pending on an auto-reset event is consumed.
+    pub fn wait_since(&self, mark: Mark) {
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        while self.generation.load(Ordering::Relaxed) == mark.generation {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+    }
+
+    /// Blocks until the event is signaled and returns how many notifies
+    /// arrived since the previous `wait_coalesced` (or since the event was
+    /// created), consuming the signal if auto-reset.
+    ///
+    /// For a single consumer of an auto-reset event whose producers may
+    /// signal faster than it drains: the count tells how many signals were
+    /// merged into this one. Every event counts its notifies, so there is no
+    /// counting mode to enable first.
+    pub fn wait_coalesced(&self) -> usize {
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        while !*guard {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+        let generation = self.generation.load(Ordering::Relaxed);
+        let last =
+            self.coalesced_generation.swap(generation, Ordering::Relaxed);
+        (generation - last) as usize
+    }
+
+    /// Waits until either this event or `cancel` is signaled. Cancellation
+    /// wins if both are.
+    pub fn wait_cancellable(&self, cancel: &Event) -> WaitOutcome {
+        let slice = [cancel, self];
+        match wait_for_any_until_impl(&slice, false, Instant::now()).unwrap() {
+            0 => WaitOutcome::Cancelled,
+            _ => WaitOutcome::Signaled,
+        }
+    }
+
+    /// Waits like `wait_cancellable`, cancelled by the event registered with
+    /// `set_global_shutdown_event`. Without one, this is a plain `wait`.
+    ///
+    /// The registry is read once on entry; a shutdown event registered while
+    /// already waiting is not observed.
+    pub fn wait_respecting_shutdown(&self) -> WaitOutcome {
+        let shutdown = GLOBAL_SHUTDOWN_EVENT.lock().unwrap().clone();
+        match shutdown {
+            Some(shutdown) => match self.wait_cancellable(&shutdown) {
+                WaitOutcome::Cancelled => WaitOutcome::ShutdownRequested,
+                outcome => outcome,
+            },
+            None => {
+                self.wait();
+                WaitOutcome::Signaled
+            },
+        }
+    }
+
+    /// Waits for the event, giving up once more than `max_spurious` wakeups
+    /// have found it still unsignaled.
+    pub fn wait_bounded_spurious(&self, max_spurious: u32) ->
+        Result<(), TooManySpurious>
+    {
+        let mut guard = self.mutex.lock();
+        let mut spurious = 0;
+        self.add_waiter();
+        while !*guard {
+            guard = self.park_locked(guard, None).0;
+            if !*guard {
+                spurious += 1;
+                if spurious > max_spurious {
+                    self.remove_waiter();
+                    return Err(TooManySpurious);
+                };
+            };
+        };
+        self.remove_waiter();
+        if self.auto_reset {
+            *guard = false;
+        };
+        Ok(())
+    }
+
+    /// Waits for the event, panicking with `msg` if `timeout` elapses first.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use event_object::Event;
+    ///
+    /// let event = Event::new(true, false).unwrap();
+    /// event.expect_within(Duration::from_millis(200), "event not signaled");
+    /// ```
+    pub fn expect_within(&self, timeout: Duration, msg: &str) {
+        if self.wait_for(timeout).timed_out() {
+            panic!("{}", msg);
+        };
+    }
+
+    pub fn require_within(&self, timeout: Duration) -> Result<(), TimedOut> {
+        if self.wait_for(timeout).timed_out() {
+            Err(TimedOut)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn notify(&self) {
+        self.notify_impl(true);
+    }
+
+    /// Notifies the event like `notify`. Retries the failed `SetEvent` calls
+    /// on Windows; signaling a condvar cannot fail, so this always
+    /// succeeds here.
+    pub fn notify_robust(&self, _retries: u32, _backoff: Duration) ->
+        Result<(), io::Error>
+    {
+        self.notify();
+        Ok(())
+    }
+
+    /// Notifies the event waking one thread blocked in `wait`, `wait_for` or
+    /// `wait_until`, then, if the event is still signaled after
+    /// `escalate_after`, wakes every waiter like `notify`. When no such
+    /// thread is blocked, every waiter is woken right away instead. The
+    /// escalations of all events share one timer thread.
+    ///
+    /// For an auto-reset event the woken waiter consumes the signal, so the
+    /// escalation only fires when it somehow didn't. A manual-reset event
+    /// stays signaled after a waiter returns: the woken thread reports
+    /// progress by calling `unnotify` in time, otherwise everyone is woken.
+    pub fn notify_escalating(self: &Arc<Self>, escalate_after: Duration) {
+        self.notify_impl(false);
+        delayed::escalate_after(self.clone(), escalate_after);
+    }
+
+    // The second step of `notify_escalating`.
+    pub(crate) fn escalate(&self) {
+        let mut guard = self.mutex.lock();
+        if *guard {
+            let externals = self.signal_locked(&mut guard);
+            drop(guard);
+            wake_external_condvars(externals);
+        };
+    }
+
+    fn notify_impl(&self, wake_all: bool) {
+        #[cfg(feature = "deadlock-detection")]
+        deadlock::record_notify(self);
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Notify);
+        let mut guard = self.mutex.lock();
+        if *guard {
+            self.redundant_notifies.fetch_add(1, Ordering::Relaxed);
+        };
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.notify_seq.store(next_notify_seq(), Ordering::Relaxed);
+        if let Some(ref stats) = self.stats {
+            stats.record_notify();
+        };
+        if let Some(ref intervals) = self.notify_intervals {
+            intervals.record_notify();
+        };
+        let externals = if wake_all {
+            self.signal_locked(&mut guard)
+        } else {
+            *guard = true;
+            self.signaled_mirror.fetch_or(true, Ordering::Relaxed);
+            if self.unpark_locked(1) == 0 {
+                self.signal_locked(&mut guard)
+            } else {
+                Vec::new()
+            }
+        };
+        #[cfg(feature = "registry")]
+        self.registry_entry.state_changed();
+        let callbacks = match *self.extras.lock() {
+            Some(ref mut extras) => mem::take(&mut extras.once_callbacks),
+            None => Vec::new(),
+        };
+        drop(guard);
+        wake_external_condvars(externals);
+        for callback in callbacks {
+            catch_callback_panic("notify_once callback", callback);
+        };
+    }
+
+    /// Notifies the event like `notify`, first publishing the signal to
+    /// `try_wait_with_ordering` with a store of the given ordering.
+    /// `Acquire` and `AcqRel`, which a store cannot take, are weakened to
+    /// `Relaxed` and `Release`.
+    ///
+    /// Pair a `Release` notify with an `Acquire` `try_wait_with_ordering` to
+    /// publish data written before the notify. On a manual-reset event a
+    /// `true` result is read without the event's lock, so with `Relaxed` on
+    /// either side the consumer may see the signal before the data. A plain
+    /// `notify` publishes nothing this way. An auto-reset event is consumed
+    /// under the lock, which orders a `true` result whatever the orderings;
+    /// there the ordering only decides how soon a lock-free `false` stops
+    /// being returned.
+    pub fn notify_with_ordering(&self, order: Ordering) {
+        self.signaled_mirror.store(true, store_ordering(order));
+        self.notify();
+    }
+
+    /// Like `try_wait`, but reads the state with `order` without taking the
+    /// event's lock. A manual-reset event is answered from that read alone;
+    /// an auto-reset event only when it reads as not signaled, and locked
+    /// to consume the signal otherwise. See `notify_with_ordering`.
+    /// `Release` and `AcqRel`, which a load cannot take, are weakened to
+    /// `Relaxed` and `Acquire`.
+    pub fn try_wait_with_ordering(&self, order: Ordering) -> bool {
+        let signaled = self.signaled_mirror.load(load_ordering(order));
+        if !signaled || !self.auto_reset {
+            return signaled;
+        };
+        let mut guard = self.mutex.lock();
+        let signaled = *guard;
+        *guard = false;
+        self.signaled_mirror.store(false, Ordering::Relaxed);
+        signaled
+    }
+
+    /// Waits like `wait`, but registered under `key`, so that it is also
+    /// woken by a `notify_except` for any other key.
+    pub fn wait_filtered(&self, key: u64) {
+        let woken = Arc::new(AtomicBool::new(false));
+        let mut guard = self.mutex.lock();
+        self.with_extras(|extras| {
+            extras.filtered_waiters.push((key, woken.clone()))
+        });
+        self.add_waiter();
+        while !*guard && !woken.load(Ordering::Relaxed) {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        self.with_extras(|extras| {
+            extras.filtered_waiters.retain(|(_, flag)| {
+                !Arc::ptr_eq(flag, &woken)
+            })
+        });
+        if *guard && self.auto_reset {
+            *guard = false;
+        };
+    }
+
+    /// Wakes every thread blocked in `wait_filtered` with a key other than
+    /// `exclude_key`, e.g. all but the thread that caused the event.
+    ///
+    /// The event itself is left as it is: plain waiters are not woken, and
+    /// a later `wait_filtered` does not see this call.
+    pub fn notify_except(&self, exclude_key: u64) {
+        let _guard = self.mutex.lock();
+        if let Some(ref extras) = *self.extras.lock() {
+            for &(key, ref woken) in extras.filtered_waiters.iter() {
+                if key != exclude_key {
+                    woken.store(true, Ordering::Relaxed);
+                };
+            };
+        };
+        self.condvar.notify_all();
+        self.unpark_locked(usize::MAX);
+    }
+
+    /// Makes every `notify` also unpark `thread`, for threads that wait with
+    /// `wait_via_park`. The registration lasts as long as the event.
+    pub fn register_unpark(&self, thread: Thread) {
+        let _guard = self.mutex.lock();
+        self.with_extras(|extras| extras.unpark_threads.push(thread));
+    }
+
+    /// Makes every `notify` also wake `waker`, so that a mio `Poll` can
+    /// treat the event as a wakeup source. The registration lasts as long
+    /// as the event; errors from `Waker::wake` are ignored.
+    #[cfg(feature = "mio")]
+    pub fn link_mio_waker(&self, waker: Arc<mio::Waker>) {
+        let _guard = self.mutex.lock();
+        self.with_extras(|extras| extras.mio_wakers.push(waker));
+    }
+
+    /// Waits by parking the current thread until the event is signaled,
+    /// consuming the signal if auto-reset. The thread must have been passed
+    /// to `register_unpark` first, or it is never woken.
+    pub fn wait_via_park(&self) {
+        while !self.try_wait() {
+            thread::park();
+        };
+    }
+
+    /// Runs `f` once, on the next `notify`, then discards it.
+    ///
+    /// A signal already pending at registration doesn't count: `f` waits for
+    /// a `notify` that comes after this call. Callbacks run on the notifying
+    /// thread once the event has been set, in registration order. A callback
+    /// that panics is logged through the `set_log_hook` hook and skipped:
+    /// the rest still run and `notify` returns normally.
+    pub fn notify_once<F: FnOnce() + Send + 'static>(&self, f: F) {
+        let _guard = self.mutex.lock();
+        self.with_extras(|extras| extras.once_callbacks.push(Box::new(f)));
+    }
+
+    /// Hands `value` to the thread blocked in `wait_for_reply` and notifies
+    /// the event.
+    ///
+    /// The slot holds a single reply, for one notifier answering one waiter;
+    /// panics if a reply is already pending.
+    pub fn notify_reply<T: Send + 'static>(&self, value: T) {
+        {
+            let _guard = self.mutex.lock();
+            self.with_extras(|extras| {
+                if extras.reply.is_some() {
+                    panic!("A reply is already pending.");
+                };
+                extras.reply = Some(Box::new(value));
+            });
+        }
+        self.notify();
+    }
+
+    /// Waits for the event to be notified through `notify_reply`, then
+    /// returns the value passed to it. Plain notifies are ignored.
+    ///
+    /// Panics if the reply is not a `T`.
+    pub fn wait_for_reply<T: Send + 'static>(&self) -> T {
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        loop {
+            if *guard {
+                let reply = match *self.extras.lock() {
+                    Some(ref mut extras) => extras.reply.take(),
+                    None => None,
+                };
+                if let Some(reply) = reply {
+                    self.remove_waiter();
+                    if self.auto_reset {
+                        *guard = false;
+                    };
+                    return downcast_reply(reply);
+                };
+            };
+            guard = self.park_locked(guard, None).0;
+        };
+    }
+
+    // Sets the event and wakes every kind of waiter, without counting it as
+    // a notify. Returns the external condvars, to be woken with
+    // `wake_external_condvars` once `mutex` is released.
+    #[must_use]
+    fn signal_locked(
+        &self,
+        guard: &mut MutexGuard<bool>
+    ) -> Vec<Arc<ExternalCondvar>> {
+        **guard = true;
+        self.signaled_mirror.fetch_or(true, Ordering::Relaxed);
+        self.condvar.notify_all();
+        self.unpark_locked(usize::MAX);
+        let externals = match *self.extras.lock() {
+            Some(ref extras) => extras.signal(),
+            None => Vec::new(),
+        };
+        if let Some(ref map) = *self.map.read() {
+            for (key, value) in map.iter() {
+                let mutex = unsafe { key.mutex.as_ref().unwrap() };
+                let condvar = unsafe { value.condvar.as_ref().unwrap() };
+                let mut guard = mutex.lock();
+                match value.kind {
+                    WaitFor::Any => *guard = value.id,
+                    WaitFor::All => *guard += value.id,
+                };
+                condvar.notify_all();
+            };
+        };
+        externals
+    }
+
+    /// Waits on `cond` with `guard`, a guard of `mutex`, like
+    /// `cond.wait(guard)`, but also returns once this event is signaled,
+    /// reporting which of the two woke the thread. The event wins if both
+    /// did; a signal of an auto-reset event is consumed.
+    ///
+    /// `cond` is registered with the event while waiting so that `notify`
+    /// wakes it. Its other waiters may see those wakeups as spurious. To
+    /// wake it, `notify` locks `mutex`, so the event must not be notified
+    /// while `mutex` is held.
+    ///
+    /// Panics if `mutex` is not locked.
+    pub fn wait_or_condvar<'a, T>(
+        &self,
+        cond: &Condvar,
+        mutex: &'a Mutex<T>,
+        guard: &mut MutexGuard<'a, T>
+    ) -> WhichWoke {
+        if mutex.try_lock().is_some() {
+            panic!("`guard` must be a guard of `mutex`.");
+        };
+        let external = Arc::new(ExternalCondvar {
+            condvar: cond as * const Condvar,
+            mutex: mutex as * const Mutex<T> as * const (),
+            wake: wake_external_condvar::<T>,
+            notifying: AtomicUsize::new(0),
+        });
+        {
+            let mut state = self.mutex.lock();
+            if *state {
+                if self.auto_reset {
+                    *state = false;
+                };
+                return WhichWoke::Event;
+            };
+            self.with_extras(|extras| {
+                extras.external_condvars.push(external.clone())
+            });
+        };
+        self.add_waiter();
+        cond.wait(guard);
+        let which = {
+            let mut state = self.mutex.lock();
+            self.with_extras(|extras| {
+                let externals = &mut extras.external_condvars;
+                let index = externals.iter()
+                    .position(|e| Arc::ptr_eq(e, &external))
+                    .unwrap();
+                externals.swap_remove(index);
+            });
+            if *state {
+                if self.auto_reset {
+                    *state = false;
+                };
+                WhichWoke::Event
+            } else {
+                WhichWoke::Condvar
+            }
+        };
+        // A notify that took `external` before it was removed may still be
+        // waiting for the mutex; it wakes `cond` once it has it.
+        while external.notifying.load(Ordering::Relaxed) > 0 {
+            cond.wait(guard);
+        };
+        self.remove_waiter();
+        which
+    }
+
+    pub fn unnotify(&self) {
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Unnotify);
+        let mut guard = self.mutex.lock();
+        self.reset_locked(&mut guard);
+    }
+
+    fn reset_locked(&self, guard: &mut MutexGuard<bool>) {
+        **guard = false;
+        self.signaled_mirror.store(false, Ordering::Relaxed);
+        self.reset_generation.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "registry")]
+        self.registry_entry.state_changed();
+        self.reset_condvar.notify_all();
+    }
+
+    /// Clears the signaled state like `unnotify`, which also records it as
+    /// a falling edge for `wait_for_reset_edge`.
+    pub fn reset_with_notification(&self) {
+        self.unnotify();
+    }
+
+    /// How many explicit resets (`unnotify`, `reset_with_notification`, a
+    /// dropped `GateGuard` closing its gate) the event has seen. Consuming
+    /// the signal of an auto-reset event is not counted.
+    pub fn reset_generation(&self) -> u64 {
+        let _guard = self.mutex.lock();
+        self.reset_generation.load(Ordering::Relaxed)
+    }
+
+    /// Blocks until the reset generation differs from `since_gen`, i.e.
+    /// until the event has been reset since `reset_generation` returned
+    /// `since_gen`, and returns the new generation.
+    pub fn wait_for_reset_edge(&self, since_gen: u64) -> u64 {
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        loop {
+            let generation = self.reset_generation.load(Ordering::Relaxed);
+            if generation != since_gen {
+                self.remove_waiter();
+                return generation;
+            };
+            self.reset_condvar.wait(&mut guard);
+        };
+    }
+
+    /// Waits for a manual-reset event used as a gate, and returns a guard
+    /// that closes the gate with `unnotify` when dropped, also on panic.
+    ///
+    /// Guards are counted: with several concurrent holders the gate closes
+    /// when the last one is dropped.
+    pub fn wait_and_hold(&self) -> GateGuard<'_> {
+        assert!(!self.auto_reset, "wait_and_hold needs a manual-reset event.");
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        while !*guard {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        self.holders.fetch_add(1, Ordering::Relaxed);
+        GateGuard { event: self }
+    }
+
+    /// Marks the event as poisoned and signals it, releasing every waiter.
+    ///
+    /// Meant for a producer that can no longer deliver the real signal, e.g.
+    /// because it panicked. `wait_poisonable` reports the poisoning; plain
+    /// waits just see the event as signaled.
+    pub fn poison(&self) {
+        {
+            let _guard = self.mutex.lock();
+            self.poisoned.store(true, Ordering::Relaxed);
+        }
+        self.notify();
+    }
+
+    /// Counts the `notify` calls that found the event already signaled.
+    ///
+    /// On a manual-reset event these are wasted signals, usually a sign of
+    /// over-signaling. An auto-reset event is left signaled only until a
+    /// waiter consumes it, so there a redundant notify means a signal was
+    /// merged into one that no waiter had taken yet.
+    pub fn redundant_notify_count(&self) -> u64 {
+        self.redundant_notifies.load(Ordering::Relaxed)
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        let _guard = self.mutex.lock();
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Returns an endless iterator performing one `wait_for(timeout)` per
+    /// item, yielding `Signaled` or `TimedOut`.
+    pub fn outcomes(&self, timeout: Duration) -> Outcomes<'_> {
+        Outcomes {
+            event: self,
+            timeout: timeout,
+        }
+    }
+
+    pub fn wait_poisonable(&self) -> Result<(), Poisoned> {
+        let mut guard = self.mutex.lock();
+        self.add_waiter();
+        while !*guard && !self.poisoned.load(Ordering::Relaxed) {
+            guard = self.park_locked(guard, None).0;
+        };
+        self.remove_waiter();
+        if self.poisoned.load(Ordering::Relaxed) {
+            return Err(Poisoned);
+        };
+        if self.auto_reset {
+            *guard = false;
+        };
+        Ok(())
+    }
 
-pub struct Event {
-    mutex: Mutex<bool>,
-    condvar: Condvar,
-    auto_reset: bool,
-    map: RwLock<OrderMap<MutexKey, CondvarWithId>>,
+    /// Returns a future that resolves once the event is signaled, for the
+    /// async-std runtime.
+    ///
+    /// On Linux the future waits on an eventfd that `notify` writes to,
+    /// registered with async-std's reactor through `Async`, so no thread
+    /// blocks. On other Unix systems a thread from async-std's
+    /// `spawn_blocking` pool blocks on a socket instead. Dropping the future
+    /// withdraws the wait.
+    #[cfg(feature = "async-std")]
+    pub fn wait_async_std(&self) -> WaitAsyncStd<'_> {
+        WaitAsyncStd {
+            event: self,
+            wake: None,
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, Hash)]
-struct MutexKey {
-    mutex: * const Mutex<usize>,
+pub struct Outcomes<'a> {
+    event: &'a Event,
+    timeout: Duration,
 }
 
-unsafe impl Send for MutexKey {}
-unsafe impl Sync for MutexKey {}
+impl<'a> Iterator for Outcomes<'a> {
+    type Item = WaitOutcome;
 
-struct CondvarWithId {
-    condvar: * const Condvar,
-    id: usize,
-    kind: WaitFor,
+    fn next(&mut self) -> Option<WaitOutcome> {
+        if self.event.wait_for(self.timeout).timed_out() {
+            Some(WaitOutcome::TimedOut)
+        } else {
+            Some(WaitOutcome::Signaled)
+        }
+    }
 }
 
-unsafe impl Send for CondvarWithId {}
-unsafe impl Sync for CondvarWithId {}
+pub struct GateGuard<'a> {
+    event: &'a Event,
+}
 
-enum WaitFor {
-    Any,
-    All,
+impl<'a> Drop for GateGuard<'a> {
+    fn drop(&mut self) {
+        let mut guard = self.event.mutex.lock();
+        if self.event.holders.fetch_sub(1, Ordering::Relaxed) == 1 {
+            self.event.reset_locked(&mut guard);
+        };
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct WaitTimeoutResult {
-    timed_out: bool,
+#[cfg(feature = "async-std")]
+pub struct WaitAsyncStd<'a> {
+    event: &'a Event,
+    wake: Option<AsyncWake>,
 }
 
-impl WaitTimeoutResult {
-    pub fn timed_out(&self) -> bool {
-        self.timed_out
+#[cfg(feature = "async-std")]
+impl<'a> WaitAsyncStd<'a> {
+    // Removes the fd of `wake` from the event before closing it, so that
+    // `notify` never writes to a closed or reused fd.
+    fn withdraw(&mut self, _guard: &MutexGuard<bool>) {
+        if let Some(wake) = self.wake.take() {
+            let fd = wake.fd();
+            self.event.with_extras(|extras| {
+                extras.async_fds.retain(|&other| other != fd)
+            });
+        };
     }
 }
 
-impl From<parking_lot::WaitTimeoutResult> for WaitTimeoutResult {
-    fn from(wtr: parking_lot::WaitTimeoutResult) -> Self {
-        WaitTimeoutResult { timed_out: wtr.timed_out() }
+#[cfg(feature = "async-std")]
+impl<'a> Future for WaitAsyncStd<'a> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let event = self.event;
+        loop {
+            {
+                let mut guard = event.mutex.lock();
+                if *guard {
+                    if event.auto_reset {
+                        *guard = false;
+                    };
+                    self.withdraw(&guard);
+                    return Poll::Ready(());
+                };
+                if self.wake.is_none() {
+                    let wake = AsyncWake::new().unwrap_or_else(|error| {
+                        panic!("Failed to wait for the event: {}.", error)
+                    });
+                    let fd = wake.fd();
+                    event.with_extras(|extras| extras.async_fds.push(fd));
+                    self.wake = Some(wake);
+                };
+            };
+            match self.wake.as_mut().unwrap().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(())) => {
+                    let guard = event.mutex.lock();
+                    self.withdraw(&guard);
+                },
+                Poll::Ready(Err(error)) => {
+                    panic!("Failed to wait for the event: {}.", error);
+                },
+            };
+        };
     }
 }
 
-impl Event {
-    pub fn new(initial_signaled: bool, auto_reset: bool) -> Result<Self, ()> {
-        Ok(Event {
-            mutex: Mutex::new(initial_signaled),
-            condvar: Condvar::new(),
-            auto_reset: auto_reset,
-            map: RwLock::new(OrderMap::new()),
-        })
+#[cfg(feature = "async-std")]
+impl<'a> Drop for WaitAsyncStd<'a> {
+    fn drop(&mut self) {
+        if self.wake.is_some() {
+            let guard = self.event.mutex.lock();
+            self.withdraw(&guard);
+        };
     }
+}
 
-    pub fn wait(&self) {
-        let mut guard = self.mutex.lock();
-        if !*guard {
-            self.condvar.wait(&mut guard);
-            assert!(*guard == true);
-        };
-        if self.auto_reset {
-            *guard = false;
+// An eventfd registered with async-std's reactor.
+#[cfg(all(feature = "async-std", target_os = "linux"))]
+struct AsyncWake {
+    eventfd: Async<File>,
+}
+
+#[cfg(all(feature = "async-std", target_os = "linux"))]
+impl AsyncWake {
+    fn new() -> io::Result<Self> {
+        let flags = libc::EFD_NONBLOCK | libc::EFD_CLOEXEC;
+        let fd = unsafe { libc::eventfd(0, flags) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
         };
+        let eventfd = Async::new(unsafe { File::from_raw_fd(fd) })?;
+        Ok(AsyncWake { eventfd: eventfd })
     }
 
-    pub fn wait_for(&self, timeout: Duration) -> WaitTimeoutResult {
-        if ChDuration::from_std(timeout.clone()).unwrap_or_else(|_e| {
-            panic!("Time period too large.");
-        }).num_milliseconds() < 0 {
-            panic!("Cannot wait for a negative time period.");
-        };
-        self.wait_until(Instant::now() + timeout)
+    fn fd(&self) -> RawFd {
+        self.eventfd.as_raw_fd()
     }
 
-    pub fn wait_until(&self, timeout: Instant) -> WaitTimeoutResult {
-        if timeout < Instant::now() {
-            panic!("Cannot wait for a previous time.");
-        };
-        let mut ret_value = WaitTimeoutResult { timed_out: false };
-        let mut guard = self.mutex.lock();
-        if !*guard {
-            let result = self.condvar.wait_until(&mut guard, timeout);
-            ret_value = WaitTimeoutResult::from(result);
-            assert!(*guard == true || ret_value.timed_out());
+    fn poll(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        let result = self.eventfd.poll_readable(cx);
+        if let Poll::Ready(Ok(())) = result {
+            let mut count = [0; 8];
+            let _ = self.eventfd.get_ref().read(&mut count);
         };
-        if self.auto_reset {
-            *guard = false;
-        };
-        ret_value
+        result
     }
+}
 
-    pub fn notify(&self) {
-        let mut guard = self.mutex.lock();
-        *guard = true;
-        self.condvar.notify_all();
-        let map = self.map.read();
-        if map.len() != 0 {
-            for (key, value) in map.iter() {
-                let mutex = unsafe { key.mutex.as_ref().unwrap() };
-                let condvar = unsafe { value.condvar.as_ref().unwrap() };
-                let mut guard = mutex.lock();
-                match value.kind {
-                    WaitFor::Any => *guard = value.id,
-                    WaitFor::All => *guard += value.id,
-                };
-                condvar.notify_all();
-            };
-        };
+// A socket pair, the reading end blocked on in a `spawn_blocking` thread.
+// Dropping the writing end ends that thread.
+#[cfg(all(feature = "async-std", not(target_os = "linux")))]
+struct AsyncWake {
+    writer: UnixStream,
+    reader: JoinHandle<()>,
+}
+
+#[cfg(all(feature = "async-std", not(target_os = "linux")))]
+impl AsyncWake {
+    fn new() -> io::Result<Self> {
+        let (mut reader, writer) = UnixStream::pair()?;
+        writer.set_nonblocking(true)?;
+        let reader = task::spawn_blocking(move || {
+            let mut bytes = [0; 8];
+            let _ = reader.read(&mut bytes);
+        });
+        Ok(AsyncWake {
+            writer: writer,
+            reader: reader,
+        })
     }
 
-    pub fn unnotify(&self) {
-        let mut guard = self.mutex.lock();
-        *guard = false;
+    fn fd(&self) -> RawFd {
+        self.writer.as_raw_fd()
+    }
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.reader).poll(cx).map(Ok)
+    }
+}
+
+// Wakes the `wait_async_std` future behind `fd`. Errors are ignored: a full
+// eventfd or socket already wakes it.
+#[cfg(feature = "async-std")]
+fn wake_async_fd(fd: RawFd) {
+    let one: u64 = 1;
+    let one = &one as *const u64 as *const libc::c_void;
+    unsafe { libc::write(fd, one, mem::size_of::<u64>()) };
+}
+
+// The strongest ordering a store or a load can take that is no stronger
+// than `order`.
+fn store_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Acquire => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Release,
+        order => order,
+    }
+}
+
+fn load_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        order => order,
+    }
+}
+
+fn downcast_reply<T: 'static>(reply: Box<dyn Any + Send>) -> T {
+    match reply.downcast() {
+        Ok(reply) => *reply,
+        Err(_) => panic!("The reply is not of the expected type."),
     }
 }
 
 pub fn wait_for_any_with(slice: &[Arc<Event>], timeout: Duration) ->
     Result<usize, WaitTimeoutResult>
 {
-    if ChDuration::from_std(timeout.clone()).unwrap_or_else(|_e| {
-        panic!("Time period too large.");
-    }).num_milliseconds() < 0 {
-        panic!("Cannot wait for a negative time period.");
-    };
-    wait_for_any_until_impl(slice, true, Instant::now() + timeout)
+    wait_for_any_until_impl(slice, true, deadline_for(timeout))
 }
 
 pub fn wait_for_any_until(slice: &[Arc<Event>], timeout: Instant) ->
@@ -156,11 +1861,144 @@ pub fn wait_for_any(slice: &[Arc<Event>]) -> usize {
     wait_for_any_until_impl(slice, false, Instant::now()).unwrap()
 }
 
-fn wait_for_any_until_impl(
-    slice: &[Arc<Event>],
+/// Returns a bitmask with bit `i` set iff `slice[i]` is currently signaled.
+/// No signal is consumed.
+pub fn poll_any_mask(slice: &[Arc<Event>]) -> u64 {
+    if slice.len() > 64 {
+        panic!("Cannot poll more than 64 events.");
+    };
+    let mut mask = 0;
+    for (id, event_ref) in slice.iter().enumerate() {
+        if *event_ref.mutex.lock() {
+            mask |= 1 << id;
+        };
+    };
+    mask
+}
+
+/// Waits for any of the events, returning the index of the signaled event
+/// with the highest priority. Ties go to the lowest index.
+pub fn wait_for_any_prioritized(slice: &[(Arc<Event>, u8)]) -> usize {
+    let events = slice.iter()
+                      .map(|(event_ref, _)| event_ref.clone())
+                      .collect::<Vec<_>>();
+    let woken = wait_for_any(&events);
+    let mut best = woken;
+    for (id, &(ref event_ref, priority)) in slice.iter().enumerate() {
+        let best_priority = slice[best].1;
+        let preferred = priority > best_priority ||
+                        (priority == best_priority && id < best);
+        if preferred && *event_ref.mutex.lock() {
+            best = id;
+        };
+    };
+    if best != woken {
+        swap_signal(&slice[best].0, &slice[woken].0);
+    };
+    best
+}
+
+/// Waits like `wait_for_any`, then picks one of the events signaled at that
+/// point at random, with a probability proportional to its weight, and
+/// consumes its signal if auto-reset. Events of weight zero are only picked
+/// if every signaled event has weight zero.
+///
+/// Draws from the thread-local generator; use
+/// `wait_for_any_weighted_lottery_with` and a seeded generator for
+/// reproducible picks.
+#[cfg(feature = "rand")]
+pub fn wait_for_any_weighted_lottery(slice: &[(Arc<Event>, u32)]) -> usize {
+    wait_for_any_weighted_lottery_with(slice, &mut rand::thread_rng())
+}
+
+/// Like `wait_for_any_weighted_lottery`, drawing from `rng`.
+#[cfg(feature = "rand")]
+pub fn wait_for_any_weighted_lottery_with<R: rand::Rng>(
+    slice: &[(Arc<Event>, u32)],
+    rng: &mut R
+) -> usize {
+    let events = slice.iter()
+                      .map(|(event_ref, _)| event_ref.clone())
+                      .collect::<Vec<_>>();
+    let woken = wait_for_any(&events);
+    let ready = slice.iter()
+                     .enumerate()
+                     .filter(|&(id, (event_ref, _))| {
+                         id == woken || *event_ref.mutex.lock()
+                     })
+                     .map(|(id, &(_, weight))| (id, u64::from(weight)))
+                     .collect::<Vec<_>>();
+    let total = ready.iter().map(|&(_, weight)| weight).sum::<u64>();
+    if total == 0 {
+        return woken;
+    };
+    let mut ticket = rng.gen_range(0, total);
+    let mut chosen = woken;
+    for (id, weight) in ready {
+        if ticket < weight {
+            chosen = id;
+            break;
+        };
+        ticket -= weight;
+    };
+    if chosen != woken {
+        swap_signal(&slice[chosen].0, &slice[woken].0);
+    };
+    chosen
+}
+
+// Waking consumed the signal of `woken` if it is auto-reset; takes the
+// signal of `chosen` instead and hands that one back, waking a single
+// waiter for it as an auto-reset `SetEvent` would.
+fn swap_signal(chosen: &Event, woken: &Event) {
+    if chosen.auto_reset {
+        *chosen.mutex.lock() = false;
+    };
+    if woken.auto_reset {
+        let mut guard = woken.mutex.lock();
+        *guard = true;
+        woken.signaled_mirror.fetch_or(true, Ordering::Relaxed);
+        if woken.unpark_locked(1) == 0 {
+            woken.condvar.notify_one();
+        };
+    };
+}
+
+/// Like `wait_for_any`, but reports invalid input as a `WaitError` instead
+/// of panicking or blocking forever.
+pub fn try_wait_for_any(slice: &[Arc<Event>]) -> Result<usize, WaitError> {
+    check_slice(slice)?;
+    Ok(wait_for_any(slice))
+}
+
+/// Like `wait_for_any_with`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_any_with(slice: &[Arc<Event>], timeout: Duration) ->
+    Result<Result<usize, WaitTimeoutResult>, WaitError>
+{
+    check_slice(slice)?;
+    let deadline = deadline_after(timeout)?;
+    Ok(wait_for_any_until_impl(slice, true, deadline))
+}
+
+/// Like `wait_for_any_until`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_any_until(slice: &[Arc<Event>], timeout: Instant) ->
+    Result<Result<usize, WaitTimeoutResult>, WaitError>
+{
+    check_slice(slice)?;
+    check_deadline(timeout)?;
+    Ok(wait_for_any_until_impl(slice, true, timeout))
+}
+
+fn wait_for_any_until_impl<E: Borrow<Event>>(
+    slice: &[E],
     with_timeout: bool,
     timeout: Instant
 ) -> Result<usize, WaitTimeoutResult> {
+    if slice.is_empty() {
+        panic!("Cannot wait for an empty set of events.");
+    };
     let mutex = Mutex::new(USIZE_MAX);
     let condvar = Condvar::new();
     let mutex_ptr = &mutex as * const Mutex<usize>;
@@ -171,16 +2009,18 @@ fn wait_for_any_until_impl(
     {
         let mut guard = mutex.lock();
         for (id, event_ref) in slice.iter().enumerate() {
-            let guard2 = event_ref.mutex.lock();
+            let event_ref = event_ref.borrow();
+            let mut guard2 = event_ref.mutex.lock();
             if *guard2 {
+                if event_ref.auto_reset {
+                    *guard2 = false;
+                };
                 for i in 0..id {
-                    let mut map = slice.get(i).unwrap().map.write();
-                    map.remove(&key);
+                    slice.get(i).unwrap().borrow().unregister(&key);
                 };
                 return Ok(id);
             };
-            let mut map = event_ref.map.write();
-            map.insert(
+            event_ref.register(
                 MutexKey { mutex: mutex_ptr },
                 CondvarWithId {
                     condvar: condvar_ptr,
@@ -207,25 +2047,53 @@ fn wait_for_any_until_impl(
         };
     };
     for event_ref in slice.iter() {
-        let mut map = event_ref.map.write();
-        map.remove(&key);
+        event_ref.borrow().unregister(&key);
     };
     if result {
         Err(WaitTimeoutResult { timed_out: true })
     } else {
+        // Like `WaitForMultipleObjects`, consume the signal of an auto-reset
+        // winner. Unlike it, a competing waiter may have taken it first.
+        let winner = slice[id].borrow();
+        if winner.auto_reset {
+            *winner.mutex.lock() = false;
+        };
         Ok(id)
     }
 }
 
+const STREAMING_CHUNK_LEN: usize = 64;
+
+/// Waits for every event like `wait_for_all`, but pulls them from `events` in
+/// chunks of 64 so that memory use does not grow with the event count.
+///
+/// A chunk is only waited on after the previous one fully signaled, so every
+/// event has to stay signaled once set: this panics on auto-reset events.
+/// Returns once each event has been observed signaled, not necessarily all
+/// at the same time.
+pub fn wait_for_all_streaming<I>(events: I)
+    where I: Iterator<Item = Arc<Event>>
+{
+    let mut chunk = Vec::with_capacity(STREAMING_CHUNK_LEN);
+    for event in events {
+        if event.auto_reset {
+            panic!("Cannot stream-wait for an auto-reset event.");
+        };
+        chunk.push(event);
+        if chunk.len() == STREAMING_CHUNK_LEN {
+            wait_for_all(&chunk);
+            chunk.clear();
+        };
+    };
+    if !chunk.is_empty() {
+        wait_for_all(&chunk);
+    };
+}
+
 pub fn wait_for_all_with(slice: &[Arc<Event>], timeout: Duration) ->
     WaitTimeoutResult
 {
-    if ChDuration::from_std(timeout.clone()).unwrap_or_else(|_e| {
-        panic!("Time period too large.");
-    }).num_milliseconds() < 0 {
-        panic!("Cannot wait for a negative time period.");
-    };
-    wait_for_all_until_impl(slice, true, Instant::now() + timeout)
+    wait_for_all_until_impl(slice, true, deadline_for(timeout))
 }
 
 pub fn wait_for_all_until(slice: &[Arc<Event>], timeout: Instant) ->
@@ -241,8 +2109,36 @@ pub fn wait_for_all(slice: &[Arc<Event>]) {
     wait_for_all_until_impl(slice, false, Instant::now());
 }
 
-fn wait_for_all_until_impl(
-    slice: &[Arc<Event>],
+/// Like `wait_for_all`, but reports invalid input as a `WaitError` instead
+/// of panicking.
+pub fn try_wait_for_all(slice: &[Arc<Event>]) -> Result<(), WaitError> {
+    check_slice(slice)?;
+    wait_for_all(slice);
+    Ok(())
+}
+
+/// Like `wait_for_all_with`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_all_with(slice: &[Arc<Event>], timeout: Duration) ->
+    Result<WaitTimeoutResult, WaitError>
+{
+    check_slice(slice)?;
+    let deadline = deadline_after(timeout)?;
+    Ok(wait_for_all_until_impl(slice, true, deadline))
+}
+
+/// Like `wait_for_all_until`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_all_until(slice: &[Arc<Event>], timeout: Instant) ->
+    Result<WaitTimeoutResult, WaitError>
+{
+    check_slice(slice)?;
+    check_deadline(timeout)?;
+    Ok(wait_for_all_until_impl(slice, true, timeout))
+}
+
+fn wait_for_all_until_impl<E: Borrow<Event>>(
+    slice: &[E],
     with_timeout: bool,
     timeout: Instant
 ) -> WaitTimeoutResult {
@@ -250,18 +2146,21 @@ fn wait_for_all_until_impl(
     let condvar = Condvar::new();
     let mutex_ptr = &mutex as * const Mutex<usize>;
     let condvar_ptr = &condvar as * const Condvar;
+    if slice.is_empty() {
+        panic!("Cannot wait for an empty set of events.");
+    };
     let from_all = (slice.len() * (slice.len() + 1)) / 2;
     let result;
     {
         let mut guard = mutex.lock();
         for (id, event_ref) in slice.iter().enumerate() {
+            let event_ref = event_ref.borrow();
             let guard2 = event_ref.mutex.lock();
             if *guard2 {
                 *guard += id + 1;
                 continue;
             };
-            let mut map = event_ref.map.write();
-            map.insert(
+            event_ref.register(
                 MutexKey { mutex: mutex_ptr },
                 CondvarWithId {
                     condvar: condvar_ptr,
@@ -287,8 +2186,520 @@ fn wait_for_all_until_impl(
     };
     let key = MutexKey { mutex: mutex_ptr };
     for event_ref in slice.iter() {
-        let mut map = event_ref.map.write();
-        map.remove(&key);
+        event_ref.borrow().unregister(&key);
+    };
+    if !result {
+        // `WaitForMultipleObjects` consumes every auto-reset signal once all
+        // are set; here that happens one event at a time afterwards.
+        for event_ref in slice.iter() {
+            let event_ref = event_ref.borrow();
+            if event_ref.auto_reset {
+                *event_ref.mutex.lock() = false;
+            };
+        };
     };
     WaitTimeoutResult { timed_out: result }
 }
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        #[cfg(feature = "deadlock-detection")]
+        deadlock::forget(self);
+        #[cfg(feature = "registry")]
+        self.registry_entry.retire();
+        self.check_no_registrations();
+    }
+}
+
+impl Event {
+    // A multi-waiter removes its registrations before returning, so any
+    // entry left behind at this point was leaked by a skipped cleanup path
+    // and holds pointers into a stack frame that no longer exists. Logged
+    // in every build, and fatal in debug builds.
+    fn check_no_registrations(&self) {
+        let ids = match *self.map.read() {
+            Some(ref map) if !map.is_empty() => {
+                map.values().map(|value| value.id).collect::<Vec<_>>()
+            },
+            _ => return,
+        };
+        let msg = format!(
+            "Event dropped with {} multi-waiter(s) still registered \
+             (ids: {:?}).",
+            ids.len(),
+            ids
+        );
+        log(&msg);
+        if cfg!(debug_assertions) && !thread::panicking() {
+            panic!("{}", msg);
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "async-std")]
+    use std::future::Future;
+    #[cfg(feature = "async-std")]
+    use std::pin::Pin;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::{Arc, Mutex as StdMutex, Weak};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    #[cfg(feature = "async-std")]
+    use std::task::{Context, Waker};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::super::parker::CondvarParker;
+    use super::{ParkResult, Parker};
+    use super::{Event, MutexKey, CondvarWithId, WaitFor, TooManySpurious};
+    use super::ConsumeResult;
+    use super::WhichWoke;
+    use super::parking_lot::{Condvar, Mutex};
+    #[cfg(target_os = "linux")]
+    use super::libc;
+
+    #[test]
+    fn test_wait_bounded_spurious() {
+        let event = Arc::new(Event::new(false, false).unwrap());
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.wait_bounded_spurious(3))
+        };
+        while !waiter.is_finished() {
+            {
+                let _guard = event.mutex.lock();
+                event.unpark_locked(1);
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(waiter.join().unwrap(), Err(TooManySpurious));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn test_dropped_wait_async_std_withdraws_fd() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let event = Event::new(false, true).unwrap();
+        {
+            let mut wait = event.wait_async_std();
+            assert!(Pin::new(&mut wait).poll(&mut cx).is_pending());
+            assert!(Pin::new(&mut wait).poll(&mut cx).is_pending());
+            let fds = event.with_extras(|extras| extras.async_fds.len());
+            assert_eq!(fds, 1);
+        };
+        assert_eq!(event.with_extras(|extras| extras.async_fds.len()), 0);
+        event.notify();
+        assert!(event.try_wait());
+    }
+
+    // Logs the calls it gets, and either times out at once or notifies the
+    // event twice from within `park`, depending on `notify_on_park`. Only
+    // the first notify has a waiter left to unpark.
+    #[derive(Default)]
+    struct MockParker {
+        calls: StdMutex<Vec<&'static str>>,
+        event: StdMutex<Weak<Event>>,
+        notify_on_park: AtomicBool,
+    }
+
+    impl Parker for MockParker {
+        fn park(&self, _deadline: Option<Instant>) -> ParkResult {
+            self.calls.lock().unwrap().push("park");
+            if !self.notify_on_park.load(Ordering::SeqCst) {
+                return ParkResult::TimedOut;
+            };
+            let event = self.event.lock().unwrap().upgrade().unwrap();
+            event.notify();
+            event.notify();
+            ParkResult::Unparked
+        }
+
+        fn unpark(&self) {
+            self.calls.lock().unwrap().push("unpark");
+        }
+    }
+
+    #[test]
+    fn test_with_parker() {
+        let parker = Arc::new(MockParker::default());
+        let event = Arc::new(Event::with_parker(false, true, parker.clone()));
+        *parker.event.lock().unwrap() = Arc::downgrade(&event);
+
+        assert!(event.wait_for(Duration::from_secs(3600)).timed_out());
+        assert_eq!(*parker.calls.lock().unwrap(), ["park"]);
+
+        parker.notify_on_park.store(true, Ordering::SeqCst);
+        event.wait();
+        assert_eq!(*parker.calls.lock().unwrap(), ["park", "park", "unpark"]);
+        assert!(!*event.mutex.lock());
+
+        event.notify();
+        event.wait();
+        assert_eq!(parker.calls.lock().unwrap().len(), 3);
+
+        let event = Arc::new(
+            Event::with_parker(false, false, Arc::new(CondvarParker::new()))
+        );
+        let waiters: Vec<_> = (0..3).map(|_| {
+            let event = event.clone();
+            thread::spawn(move || event.wait())
+        }).collect();
+        thread::sleep(Duration::from_millis(50));
+        event.notify();
+        for waiter in waiters {
+            waiter.join().unwrap();
+        };
+    }
+
+    #[test]
+    fn test_with_parker_covers_single_event_waits() {
+        let parker = Arc::new(MockParker::default());
+        let event = Arc::new(Event::with_parker(false, true, parker.clone()));
+        *parker.event.lock().unwrap() = Arc::downgrade(&event);
+        parker.notify_on_park.store(true, Ordering::SeqCst);
+        let unset = AtomicBool::new(false);
+        let waits: Vec<Box<dyn Fn()>> = vec![
+            Box::new(|| { event.wait_consuming(); }),
+            Box::new(|| { event.wait_seq(); }),
+            Box::new(|| { event.wait_unless(&unset); }),
+            Box::new(|| event.wait_since(event.mark())),
+            Box::new(|| { event.wait_coalesced(); }),
+            Box::new(|| event.wait_filtered(1)),
+            Box::new(|| { event.wait_diagnostic(None); }),
+            Box::new(|| event.wait_bounded_spurious(0).unwrap()),
+            Box::new(|| event.wait_poisonable().unwrap()),
+        ];
+        for wait in waits {
+            parker.calls.lock().unwrap().clear();
+            wait();
+            assert_eq!(*parker.calls.lock().unwrap(), ["park", "unpark"]);
+            assert!(!*event.mutex.lock());
+        };
+    }
+
+    #[test]
+    fn test_unnotify_does_not_wake_waiters() {
+        let event = Arc::new(
+            super::super::EventBuilder::new().stats(true).build().unwrap()
+        );
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.wait_bounded_spurious(0))
+        };
+        while event.waiters.load(Ordering::Relaxed) == 0 {
+            thread::yield_now();
+        };
+        for _ in 0..10 {
+            event.unnotify();
+        };
+        event.notify();
+        assert_eq!(waiter.join().unwrap(), Ok(()));
+        assert_eq!(event.stats().spurious_wakeups(), 0);
+    }
+
+    #[test]
+    fn test_wait_consuming() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let waiters: Vec<_> = (0..4).map(|_| {
+            let event = event.clone();
+            thread::spawn(move || event.wait_consuming())
+        }).collect();
+        while event.waiters.load(Ordering::Relaxed) < 4 {
+            thread::yield_now();
+        };
+        event.notify();
+        let consumed = waiters.into_iter()
+            .map(|waiter| waiter.join().unwrap())
+            .filter(|result| *result == ConsumeResult::Consumed)
+            .count();
+        assert_eq!(consumed, 1);
+        assert!(!*event.mutex.lock());
+
+        let event = Event::new(true, false).unwrap();
+        assert_eq!(event.wait_consuming(), ConsumeResult::Observed);
+    }
+
+    #[test]
+    fn test_wait_rate_limited() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let done = Arc::new(AtomicBool::new(false));
+        let producer = {
+            let event = event.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    event.notify();
+                    thread::sleep(Duration::from_millis(2));
+                };
+            })
+        };
+        let min_interval = Duration::from_millis(50);
+        let returns: Vec<_> = (0..4).map(|_| {
+            event.wait_rate_limited(min_interval);
+            Instant::now()
+        }).collect();
+        done.store(true, Ordering::SeqCst);
+        producer.join().unwrap();
+        for pair in returns.windows(2) {
+            assert!(pair[1] - pair[0] >= min_interval);
+        };
+    }
+
+    #[test]
+    fn test_notify_escalating() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.wait())
+        };
+        let multi_waiter = {
+            let events = vec![event.clone()];
+            thread::spawn(move || {
+                super::wait_for_any_with(&events, Duration::from_millis(500))
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        event.notify_escalating(Duration::from_millis(100));
+        waiter.join().unwrap();
+        assert!(multi_waiter.join().unwrap().is_err());
+
+        let events = vec![event.clone()];
+        let multi_waiter = thread::spawn(move || {
+            let start = Instant::now();
+            let timeout = Duration::from_secs(5);
+            let result = super::wait_for_any_with(&events, timeout);
+            (result, start.elapsed())
+        });
+        thread::sleep(Duration::from_millis(100));
+        event.notify_escalating(Duration::from_secs(5));
+        let (result, elapsed) = multi_waiter.join().unwrap();
+        assert_eq!(result, Ok(0));
+        assert!(elapsed < Duration::from_secs(5));
+
+        let event = Arc::new(Event::new(false, false).unwrap());
+        let waiters: Vec<_> = (0..2).map(|_| {
+            let event = event.clone();
+            thread::spawn(move || {
+                event.wait();
+                Instant::now()
+            })
+        }).collect();
+        thread::sleep(Duration::from_millis(100));
+        let notified = Instant::now();
+        event.notify_escalating(Duration::from_millis(300));
+        let mut woken: Vec<_> = waiters.into_iter()
+            .map(|waiter| waiter.join().unwrap() - notified)
+            .collect();
+        woken.sort();
+        assert!(woken[0] < Duration::from_millis(300));
+        assert!(woken[1] >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let mutex = Arc::new(Mutex::new(false));
+        let condvar = Arc::new(Condvar::new());
+        let event = Event::from_parts(mutex.clone(), condvar.clone(), true);
+        let waiter = thread::spawn(move || {
+            assert!(!event.wait_for(Duration::from_secs(5)).timed_out());
+            event
+        });
+        thread::sleep(Duration::from_millis(100));
+        *mutex.lock() = true;
+        condvar.notify_all();
+        let event = waiter.join().unwrap();
+        assert!(!*mutex.lock());
+
+        event.notify();
+        let mut guard = mutex.lock();
+        assert!(*guard);
+        *guard = false;
+    }
+
+    #[test]
+    fn test_wait_adaptive() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        event.set_adaptive_default(Duration::from_secs(5));
+        let notifier = {
+            let event = event.clone();
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    thread::sleep(Duration::from_millis(20));
+                    event.notify();
+                };
+            })
+        };
+        for _ in 0..5 {
+            assert!(!event.wait_adaptive(5.0).timed_out());
+        };
+        notifier.join().unwrap();
+        let start = Instant::now();
+        assert!(event.wait_adaptive(5.0).timed_out());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_wait_or_condvar() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let queue = Arc::new((Mutex::new(Vec::new()), Condvar::new()));
+
+        let waiter = {
+            let event = event.clone();
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let mut guard = queue.0.lock();
+                let which =
+                    event.wait_or_condvar(&queue.1, &queue.0, &mut guard);
+                (which, guard.len())
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        queue.0.lock().push(1);
+        queue.1.notify_one();
+        assert_eq!(waiter.join().unwrap(), (WhichWoke::Condvar, 1));
+
+        let waiter = {
+            let event = event.clone();
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let mut guard = queue.0.lock();
+                event.wait_or_condvar(&queue.1, &queue.0, &mut guard)
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        event.notify();
+        assert_eq!(waiter.join().unwrap(), WhichWoke::Event);
+        assert!(!event.try_wait());
+        let externals =
+            event.with_extras(|extras| extras.external_condvars.len());
+        assert_eq!(externals, 0);
+
+        // A notify racing with the start of the wait must not be lost.
+        for _ in 0..200 {
+            let waiter = {
+                let event = event.clone();
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut guard = queue.0.lock();
+                    event.wait_or_condvar(&queue.1, &queue.0, &mut guard)
+                })
+            };
+            event.notify();
+            assert_eq!(waiter.join().unwrap(), WhichWoke::Event);
+        };
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wait_boosted_restores_priority() {
+        let tid = unsafe { libc::syscall(libc::SYS_gettid) } as libc::id_t;
+        let before = super::current_nice(tid);
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let notifier = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                event.notify();
+            })
+        };
+        event.wait_boosted();
+        notifier.join().unwrap();
+        assert_eq!(super::current_nice(tid), before);
+    }
+
+    #[test]
+    fn test_wait_with_ordering() {
+        for _ in 0..200 {
+            let event = Arc::new(Event::new(false, true).unwrap());
+            let data = Arc::new(AtomicUsize::new(0));
+            let producer = {
+                let event = event.clone();
+                let data = data.clone();
+                thread::spawn(move || {
+                    data.store(42, Ordering::Relaxed);
+                    event.notify_with_ordering(Ordering::Release);
+                })
+            };
+            while !event.try_wait_with_ordering(Ordering::Acquire) {
+                thread::yield_now();
+            };
+            assert_eq!(data.load(Ordering::Relaxed), 42);
+            assert!(!event.try_wait_with_ordering(Ordering::Acquire));
+            producer.join().unwrap();
+        };
+
+        for _ in 0..200 {
+            let event = Arc::new(Event::new(false, false).unwrap());
+            let data = Arc::new(AtomicUsize::new(0));
+            let producer = {
+                let event = event.clone();
+                let data = data.clone();
+                thread::spawn(move || {
+                    data.store(42, Ordering::Relaxed);
+                    event.notify_with_ordering(Ordering::Release);
+                })
+            };
+            while !event.try_wait_with_ordering(Ordering::Acquire) {
+                thread::yield_now();
+            };
+            assert_eq!(data.load(Ordering::Relaxed), 42);
+            producer.join().unwrap();
+        };
+
+        let event = Event::new(false, false).unwrap();
+        event.notify_with_ordering(Ordering::Acquire);
+        event.notify_with_ordering(Ordering::AcqRel);
+        {
+            // A manual-reset event is answered without the lock.
+            let _guard = event.mutex.lock();
+            assert!(event.try_wait_with_ordering(Ordering::Release));
+            assert!(event.try_wait_with_ordering(Ordering::AcqRel));
+        }
+        event.unnotify();
+        assert!(!event.try_wait_with_ordering(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_lazy_waiter_map() {
+        let event_vec = vec![
+            Arc::new(Event::new(false, false).unwrap()),
+            Arc::new(Event::new(false, false).unwrap()),
+        ];
+        assert!(event_vec.iter().all(|event| event.map.read().is_none()));
+        let timeout = Duration::from_millis(10);
+        assert!(super::wait_for_any_with(&event_vec, timeout).is_err());
+        assert!(event_vec.iter().all(|event| event.map.read().is_some()));
+
+        let waiter = {
+            let event_vec = event_vec.clone();
+            thread::spawn(move || super::wait_for_all(&event_vec))
+        };
+        thread::sleep(Duration::from_millis(100));
+        event_vec[1].notify();
+        assert_eq!(super::wait_for_any(&event_vec), 1);
+        event_vec[0].notify();
+        waiter.join().unwrap();
+    }
+
+    #[test]
+    fn test_drop_with_leaked_registration() {
+        super::super::tests::record_logs();
+        let event = Event::new(false, false).unwrap();
+        let mutex = Mutex::new(0usize);
+        let condvar = Condvar::new();
+        event.register(
+            MutexKey { mutex: &mutex as * const Mutex<usize> },
+            CondvarWithId {
+                condvar: &condvar as * const Condvar,
+                id: 3,
+                kind: WaitFor::Any
+            }
+        );
+        let dropped = panic::catch_unwind(AssertUnwindSafe(|| drop(event)));
+        assert_eq!(dropped.is_err(), cfg!(debug_assertions));
+        let msg =
+            "Event dropped with 1 multi-waiter(s) still registered (ids: [3]).";
+        assert_eq!(super::super::tests::logged(msg), 1);
+    }
+}
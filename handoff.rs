@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::Event;
+
+impl Event {
+    /// Waits for the event like `wait`, then notifies `next`: one stage of
+    /// a pipeline handing its turn to the following one.
+    pub fn wait_then_notify(&self, next: &Event) {
+        self.wait();
+        next.notify();
+    }
+
+    /// Claims one unit of work from `counter`, waiting for the event while
+    /// there is none: for workers woken by a "work available" event whose
+    /// producer increments `counter` before each `notify`.
+    ///
+    /// A claim is tried before each wait, so units announced by merged
+    /// signals are not stranded, and a claim lost to another worker re-arms
+    /// the wait. Returns `None` once the event is poisoned and no unit is
+    /// left, e.g. to shut the workers down.
+    pub fn wait_claim(&self, counter: &AtomicUsize) -> Option<()> {
+        loop {
+            let claimed = counter.fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |units| units.checked_sub(1)
+            );
+            if claimed.is_ok() {
+                return Some(());
+            };
+            if self.wait_poisonable().is_err() {
+                return None;
+            };
+        };
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::Event;
+
+// The children registered with a parent event, and how many have fired.
+#[derive(Debug, Default)]
+pub struct Children {
+    registered: AtomicUsize,
+    fired: AtomicUsize,
+}
+
+impl Event {
+    /// Creates a manual-reset event meant to be the root of a completion
+    /// tree; see `add_child`.
+    pub fn new_parent() -> Arc<Event> {
+        Arc::new(Event::new(false, false).unwrap())
+    }
+
+    /// Creates a manual-reset child of `parent`. Once every child registered
+    /// so far has been notified, `parent` is notified, exactly once.
+    ///
+    /// Register all children before notifying any: a parent whose children
+    /// have all fired completes even if more are added later. Only the first
+    /// notify of a child counts. Children can be parents in turn.
+    pub fn add_child(parent: &Arc<Event>) -> Arc<Event> {
+        parent.children().registered.fetch_add(1, Ordering::SeqCst);
+        let child = Arc::new(Event::new(false, false).unwrap());
+        let parent = parent.clone();
+        child.notify_once(move || {
+            let children = parent.children();
+            let fired = children.fired.fetch_add(1, Ordering::SeqCst) + 1;
+            if fired == children.registered.load(Ordering::SeqCst) {
+                parent.notify();
+            };
+        });
+        child
+    }
+}
@@ -1,3 +1,5 @@
+// Struct literals spell out `field: field` throughout the crate.
+#![allow(clippy::redundant_field_names)]
 
 #[cfg(not(windows))]
 #[path = "generic.rs"]
@@ -7,23 +9,157 @@ mod implement;
 #[path = "windows.rs"]
 mod implement;
 
-pub use implement::{Event, WaitTimeoutResult};
+#[cfg(feature = "async-std")]
+mod async_cancel;
+mod bounded;
+mod builder;
+#[cfg(feature = "deadlock-detection")]
+mod deadlock;
+mod deadline;
+mod delayed;
+mod forward;
+mod handoff;
+mod hierarchy;
+mod log;
+#[cfg(not(windows))]
+mod parker;
+mod ping_pong;
+mod proof;
+#[cfg(feature = "registry")]
+mod registry;
+mod scope;
+#[cfg(target_os = "linux")]
+mod shared;
+mod stats;
+mod threads;
+mod timed;
+mod timeout;
+#[cfg(feature = "trace")]
+mod trace;
+mod wait_stack;
+mod watcher;
+
+pub use implement::{Event, WaitTimeoutResult, TimedOut, Poisoned};
+pub use implement::{TooManySpurious, WaitDiagnostic, WaitOutcome};
+pub use implement::{EventState, EventError, Mark, Outcomes, WaitError};
+pub use implement::GateGuard;
 pub use implement::{wait_for_any, wait_for_all};
+pub use implement::{poll_any_mask, wait_for_any_prioritized};
+pub use implement::wait_for_all_streaming;
+pub use implement::{try_wait_for_any, try_wait_for_all};
+pub use implement::{try_wait_for_any_with, try_wait_for_all_with};
+pub use implement::{try_wait_for_any_until, try_wait_for_all_until};
+pub use implement::set_global_shutdown_event;
+#[cfg(feature = "rand")]
+pub use implement::wait_for_any_weighted_lottery;
+#[cfg(feature = "rand")]
+pub use implement::wait_for_any_weighted_lottery_with;
 pub use implement::{wait_for_any_with, wait_for_all_with};
 pub use implement::{wait_for_any_until, wait_for_all_until};
 
+#[cfg(windows)]
+pub use implement::EventAccess;
+
+#[cfg(not(windows))]
+pub use implement::{ConsumeResult, WhichWoke};
+
+#[cfg(feature = "async-std")]
+pub use implement::WaitAsyncStd;
+#[cfg(feature = "async-std")]
+pub use async_cancel::WaitAsyncCancellable;
+
+pub use bounded::BoundedEvent;
+pub use builder::EventBuilder;
+#[cfg(feature = "deadlock-detection")]
+pub use deadlock::{EventId, detect_cycles};
+pub use deadline::{Deadline, HardTimeout};
+pub use deadline::{wait_for_any_within, wait_for_all_within};
+pub use delayed::DelayedNotifier;
+pub use forward::Forwarder;
+pub use log::set_log_hook;
+#[cfg(not(windows))]
+pub use parker::{CondvarParker, ParkResult, Parker};
+pub use ping_pong::PingPong;
+pub use proof::Signaled;
+#[cfg(feature = "registry")]
+pub use registry::dump_all_events;
+pub use scope::ScopeSignal;
+#[cfg(target_os = "linux")]
+pub use shared::SharedEvent;
+pub use stats::EventStats;
+pub use threads::set_thread_name_prefix;
+pub use timed::TimedEvent;
+pub use timeout::{WaitMs, duration_to_wait_ms, wait_ms_to_duration};
+#[cfg(feature = "trace")]
+pub use trace::{TraceEvent, TraceOp, export_trace, replay};
+pub use wait_stack::{WaitFrame, dump_wait_stacks};
+pub use watcher::WeakWatcher;
+
+#[cfg(test)]
+mod conformance;
+
 #[cfg(test)]
 mod tests {
     extern crate crossbeam;
     extern crate rand;
+    #[cfg(feature = "async-std")]
+    extern crate async_std;
+    #[cfg(feature = "mio")]
+    extern crate mio;
 
-    use std::sync::Arc;
-    use std::time::Duration;
+    #[cfg(target_os = "linux")]
+    use std::env;
+    use std::panic::{self, AssertUnwindSafe};
+    #[cfg(target_os = "linux")]
+    use std::process::{self, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use std::sync::mpsc;
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
 
     use self::rand::{Rng, OsRng};
-    use super::{Event, wait_for_any, wait_for_all};
+    #[cfg(feature = "rand")]
+    use self::rand::{SeedableRng, XorShiftRng};
+    #[cfg(feature = "rand")]
+    use super::wait_for_any_weighted_lottery;
+    #[cfg(feature = "rand")]
+    use super::wait_for_any_weighted_lottery_with;
+    use super::{Event, EventState, TimedOut, Poisoned, WaitOutcome};
+    use super::{BoundedEvent, DelayedNotifier};
+    use super::{set_global_shutdown_event, set_thread_name_prefix};
+    use super::threads::spawn_named;
+    use super::{wait_for_any, wait_for_all, wait_for_any_prioritized};
+    use super::{poll_any_mask, wait_for_all_streaming};
+    use super::{try_wait_for_any, try_wait_for_all, WaitError};
+    use super::{try_wait_for_any_with, try_wait_for_all_until};
+    use super::{EventBuilder, set_log_hook};
+    use super::{WaitMs, duration_to_wait_ms, wait_ms_to_duration};
+    use super::{Deadline, HardTimeout};
+    #[cfg(target_os = "linux")]
+    use super::SharedEvent;
+    use super::{WaitFrame, dump_wait_stacks};
+    #[cfg(feature = "deadlock-detection")]
+    use super::detect_cycles;
+    #[cfg(feature = "registry")]
+    use super::dump_all_events;
+    #[cfg(feature = "trace")]
+    use super::{TraceOp, export_trace, replay};
+    use super::{wait_for_any_within, wait_for_all_within};
     use super::{wait_for_any_with, wait_for_all_with};
 
+    #[test]
+    fn test_capture_state() {
+        let event = Event::new(true, true).unwrap();
+        let state = event.capture_state();
+        assert_eq!(state, EventState { signaled: true, auto_reset: true });
+        let restored = Event::from_state(state).unwrap();
+        assert_eq!(restored.capture_state(), state);
+        assert!(event.try_wait());
+        assert!(restored.try_wait());
+        assert!(!restored.capture_state().signaled);
+    }
+
     #[test]
     fn test_wait() {
         let event = Event::new(false, false).unwrap();
@@ -42,6 +178,219 @@ mod tests {
         assert!(result.timed_out());
     }
 
+    #[test]
+    #[should_panic(expected = "event not signaled")]
+    fn test_expect_within() {
+        let event = Event::new(false, false).unwrap();
+        event.expect_within(Duration::from_millis(200), "event not signaled");
+    }
+
+    #[test]
+    fn test_require_within() {
+        let event = Event::new(false, false).unwrap();
+        let result = event.require_within(Duration::from_millis(200));
+        assert_eq!(result, Err(TimedOut));
+        event.notify();
+        assert!(event.require_within(Duration::from_millis(200)).is_ok());
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn test_wait_async_std() {
+        let event = Event::new(false, true).unwrap();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                event.notify();
+            });
+            async_std::task::block_on(event.wait_async_std());
+        });
+    }
+
+    #[cfg(feature = "mio")]
+    #[test]
+    fn test_link_mio_waker() {
+        let mut poll = mio::Poll::new().unwrap();
+        let token = mio::Token(7);
+        let waker = mio::Waker::new(poll.registry(), token).unwrap();
+        let event = Event::new(false, false).unwrap();
+        event.link_mio_waker(Arc::new(waker));
+        let mut events = mio::Events::with_capacity(4);
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                event.notify();
+            });
+            poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        });
+        assert!(events.iter().any(|mio_event| mio_event.token() == token));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn test_wait_async_cancellable() {
+        let event = Event::new(false, true).unwrap();
+        let cancel = async_std::task::sleep(Duration::from_millis(50));
+        let outcome =
+            async_std::task::block_on(event.wait_async_cancellable(cancel));
+        assert_eq!(outcome, WaitOutcome::Cancelled);
+        assert!(!event.try_wait());
+        event.notify();
+        let cancel = async_std::task::sleep(Duration::from_secs(10));
+        let outcome =
+            async_std::task::block_on(event.wait_async_cancellable(cancel));
+        assert_eq!(outcome, WaitOutcome::Signaled);
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let event = Event::new(true, true).unwrap();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(500));
+                event.notify();
+            });
+            let outcomes = event.outcomes(Duration::from_millis(200))
+                                .take(4)
+                                .collect::<Vec<_>>();
+            assert_eq!(outcomes, vec![
+                WaitOutcome::Signaled,
+                WaitOutcome::TimedOut,
+                WaitOutcome::TimedOut,
+                WaitOutcome::Signaled,
+            ]);
+        });
+    }
+
+    #[test]
+    fn test_poll_or_yield() {
+        let event = Event::new(false, true).unwrap();
+        let mut yields = 0;
+        while !event.poll_or_yield(|| {
+            yields += 1;
+            if yields == 3 {
+                event.notify();
+            };
+        }) {};
+        assert_eq!(yields, 3);
+        assert!(!event.try_wait());
+    }
+
+    #[test]
+    fn test_wait_poisonable() {
+        let event = Event::new(false, true).unwrap();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                event.poison();
+            });
+            assert_eq!(event.wait_poisonable(), Err(Poisoned));
+        });
+        assert!(event.is_poisoned());
+        assert_eq!(event.wait_poisonable(), Err(Poisoned));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_new_with_access() {
+        use std::io::ErrorKind;
+        use super::{EventAccess, EventError};
+
+        let event = Event::new_with_access(
+            false,
+            false,
+            EventAccess::MODIFY_STATE
+        ).unwrap();
+        event.notify();
+        match event.wait_checked() {
+            Err(EventError::Os(error)) => {
+                assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+            },
+            result => panic!("unexpected wait result: {:?}", result),
+        };
+    }
+
+    #[cfg(windows)]
+    #[test]
+    #[should_panic(expected = "Failed to wait for the event")]
+    fn test_wait_without_synchronize_panics() {
+        use super::EventAccess;
+
+        let event = Event::new_with_access(
+            false,
+            false,
+            EventAccess::MODIFY_STATE
+        ).unwrap();
+        event.notify();
+        event.wait_for(Duration::from_secs(1));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    #[should_panic(expected = "Failed to wait for the event")]
+    fn test_wait_for_any_without_synchronize_panics() {
+        use super::EventAccess;
+
+        let event = Event::new_with_access(
+            false,
+            false,
+            EventAccess::MODIFY_STATE
+        ).unwrap();
+        let events = vec![
+            Arc::new(Event::new(false, false).unwrap()),
+            Arc::new(event),
+        ];
+        wait_for_any(&events);
+    }
+
+    #[test]
+    fn test_wait_respecting_shutdown() {
+        // The shutdown event is process-wide; replace the signaled one with
+        // a fresh event for the other tests, also on failure.
+        struct ReplaceShutdown;
+        impl Drop for ReplaceShutdown {
+            fn drop(&mut self) {
+                let event = Event::new(false, false).unwrap();
+                set_global_shutdown_event(Arc::new(event));
+            }
+        }
+
+        let shutdown = Arc::new(Event::new(false, false).unwrap());
+        let _replace = ReplaceShutdown;
+        set_global_shutdown_event(shutdown.clone());
+        let event = Event::new(false, false).unwrap();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                shutdown.notify();
+            });
+            assert_eq!(
+                event.wait_respecting_shutdown(),
+                WaitOutcome::ShutdownRequested
+            );
+        });
+    }
+
+    #[test]
+    fn test_wait_since() {
+        let event = Event::new(false, true).unwrap();
+        let mark = event.mark();
+        event.notify();
+        assert!(event.try_wait());
+        event.wait_since(mark);
+
+        let mark = event.mark();
+        crossbeam::scope(|scope| {
+            let start = Instant::now();
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                event.notify();
+            });
+            event.wait_since(mark);
+            assert!(start.elapsed() >= Duration::from_millis(100));
+        });
+    }
+
     #[test]
     fn test_wait_for_any() {
         let mut event_vec = vec![];
@@ -62,6 +411,57 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_poll_any_mask() {
+        let mut event_vec = vec![];
+        for i in 0..6 {
+            let signaled = i == 0 || i == 2 || i == 5;
+            event_vec.push(Arc::new(Event::new(signaled, true).unwrap()));
+        };
+        assert_eq!(poll_any_mask(&event_vec), 0b100101);
+        assert_eq!(poll_any_mask(&event_vec), 0b100101);
+    }
+
+    #[test]
+    fn test_wait_for_any_prioritized() {
+        let event_vec = vec![
+            (Arc::new(Event::new(true, false).unwrap()), 1),
+            (Arc::new(Event::new(false, false).unwrap()), 5),
+            (Arc::new(Event::new(true, false).unwrap()), 9),
+            (Arc::new(Event::new(true, false).unwrap()), 9),
+        ];
+        assert_eq!(2, wait_for_any_prioritized(&event_vec));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_wait_for_any_weighted_lottery() {
+        let event_vec = vec![
+            (Arc::new(Event::new(true, false).unwrap()), 1),
+            (Arc::new(Event::new(false, false).unwrap()), 50),
+            (Arc::new(Event::new(true, false).unwrap()), 3),
+            (Arc::new(Event::new(true, false).unwrap()), 0),
+        ];
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let mut counts = [0; 4];
+        for _ in 0..4000 {
+            let chosen =
+                wait_for_any_weighted_lottery_with(&event_vec, &mut rng);
+            counts[chosen] += 1;
+        };
+        assert_eq!(counts[1] + counts[3], 0);
+        assert!(counts[0] > 800 && counts[0] < 1200);
+        assert!(wait_for_any_weighted_lottery(&event_vec) != 1);
+
+        let auto_vec = vec![
+            (Arc::new(Event::new(true, true).unwrap()), 0),
+            (Arc::new(Event::new(true, true).unwrap()), 1),
+        ];
+        assert_eq!(wait_for_any_weighted_lottery(&auto_vec), 1);
+        assert!(auto_vec[0].0.try_wait());
+        assert!(!auto_vec[1].0.try_wait());
+    }
+
     #[test]
     fn test_wait_for_any_with() {
         let mut event_vec = vec![];
@@ -89,6 +489,1060 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_wait_for_all_streaming() {
+        let mut event_vec = vec![];
+        for _ in 0..1000 {
+            event_vec.push(Arc::new(Event::new(false, false).unwrap()));
+        };
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                for event_ref in event_vec.iter().rev() {
+                    event_ref.notify();
+                };
+            });
+            wait_for_all_streaming(event_vec.iter().cloned());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "auto-reset")]
+    fn test_wait_for_all_streaming_auto_reset() {
+        let event = Arc::new(Event::new(true, true).unwrap());
+        wait_for_all_streaming(vec![event].into_iter());
+    }
+
+    #[test]
+    fn test_try_wait_errors() {
+        let no_events: Vec<Arc<Event>> = vec![];
+        assert_eq!(try_wait_for_any(&no_events), Err(WaitError::EmptySlice));
+        assert_eq!(try_wait_for_all(&no_events), Err(WaitError::EmptySlice));
+
+        let event = Event::new(false, false).unwrap();
+        let too_large = Duration::from_secs(u64::MAX);
+        assert_eq!(
+            event.try_wait_for(too_large),
+            Err(WaitError::TimeoutTooLarge)
+        );
+        let event_vec = vec![Arc::new(Event::new(false, false).unwrap())];
+        assert_eq!(
+            try_wait_for_any_with(&event_vec, too_large),
+            Err(WaitError::TimeoutTooLarge)
+        );
+
+        let past = Instant::now() - Duration::from_millis(100);
+        assert_eq!(event.try_wait_until(past), Err(WaitError::NegativeTimeout));
+        assert_eq!(
+            try_wait_for_all_until(&event_vec, past),
+            Err(WaitError::NegativeTimeout)
+        );
+
+        let result = event.try_wait_for(Duration::from_millis(100));
+        assert!(result.unwrap().timed_out());
+    }
+
+    #[test]
+    fn test_stats() {
+        let event = EventBuilder::new().auto_reset(true).stats(true).build()
+            .unwrap();
+        for _ in 0..3 {
+            event.notify();
+            event.wait();
+        };
+        for _ in 0..2 {
+            let result = event.wait_for(Duration::from_millis(20));
+            assert!(result.timed_out());
+        };
+        event.notify();
+        assert!(!event.wait_for(Duration::from_millis(20)).timed_out());
+
+        let stats = event.stats();
+        assert_eq!(stats.waits(), 6);
+        assert_eq!(stats.notifies(), 4);
+        assert_eq!(stats.timeouts(), 2);
+        assert!(stats.wait_time() >= Duration::from_millis(40));
+
+        stats.reset();
+        assert_eq!(event.stats().waits(), 0);
+        assert_eq!(event.stats().wait_time(), Duration::from_secs(0));
+
+        let plain = Event::new(false, false).unwrap();
+        plain.notify();
+        plain.wait();
+        assert_eq!(plain.stats().waits(), 0);
+        assert_eq!(plain.stats().notifies(), 0);
+    }
+
+    #[test]
+    fn test_deadline_multi_wait() {
+        let any_vec = vec![
+            Arc::new(Event::new(false, false).unwrap()),
+            Arc::new(Event::new(false, false).unwrap()),
+        ];
+        let all_vec = vec![
+            Arc::new(Event::new(true, false).unwrap()),
+            Arc::new(Event::new(false, false).unwrap()),
+        ];
+        let start = Instant::now();
+        let deadline = Deadline::after(Duration::from_millis(300));
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                any_vec[1].notify();
+            });
+            assert_eq!(wait_for_any_within(&any_vec, &deadline), Ok(1));
+            assert!(wait_for_all_within(&all_vec, &deadline).timed_out());
+        });
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_millis(450));
+
+        assert!(deadline.is_expired());
+        assert_eq!(deadline.remaining(), Duration::from_secs(0));
+        assert!(any_vec[0].wait_within(&deadline).timed_out());
+        assert!(wait_for_any_within(&any_vec, &deadline).is_err());
+    }
+
+    #[test]
+    fn test_redundant_notify_count() {
+        let event = Event::new(false, false).unwrap();
+        event.notify();
+        assert_eq!(event.redundant_notify_count(), 0);
+        for _ in 0..3 {
+            event.notify();
+        };
+        assert_eq!(event.redundant_notify_count(), 3);
+        event.unnotify();
+        event.notify();
+        assert_eq!(event.redundant_notify_count(), 3);
+
+        let auto_event = Event::new(false, true).unwrap();
+        auto_event.notify();
+        auto_event.wait();
+        auto_event.notify();
+        assert_eq!(auto_event.redundant_notify_count(), 0);
+        auto_event.notify();
+        assert_eq!(auto_event.redundant_notify_count(), 1);
+    }
+
+    #[test]
+    fn test_wait_labeled() {
+        let event = Event::new(false, true).unwrap();
+        let frame = WaitFrame {
+            event: &event as *const Event as usize,
+            label: "labeled".to_owned(),
+        };
+        let dumped = || dump_wait_stacks().iter().any(|(_, frames)| {
+            frames.contains(&frame)
+        });
+        assert!(!dumped());
+        crossbeam::scope(|scope| {
+            scope.spawn(|| event.wait_labeled("labeled"));
+            while !dumped() {
+                thread::yield_now();
+            };
+            event.notify();
+        });
+        assert!(!dumped());
+    }
+
+    #[cfg(feature = "deadlock-detection")]
+    #[test]
+    fn test_detect_cycles() {
+        let a = Event::new(false, false).unwrap();
+        let b = Event::new(false, false).unwrap();
+        let mut expected = vec![a.id(), b.id()];
+        expected.sort();
+        let has_cycle = || detect_cycles().contains(&expected);
+        assert!(!has_cycle());
+        let barrier = std::sync::Barrier::new(2);
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                a.notify();
+                a.unnotify();
+                barrier.wait();
+                b.wait();
+            });
+            scope.spawn(|| {
+                b.notify();
+                b.unnotify();
+                barrier.wait();
+                a.wait();
+            });
+            while !has_cycle() {
+                thread::sleep(Duration::from_millis(10));
+            };
+            a.notify();
+            b.notify();
+        });
+        assert!(!has_cycle());
+    }
+
+    #[test]
+    fn test_reply() {
+        for &auto_reset in &[false, true] {
+            let event = Event::new(false, auto_reset).unwrap();
+            crossbeam::scope(|scope| {
+                scope.spawn(|| {
+                    thread::sleep(Duration::from_millis(50));
+                    event.notify();
+                    thread::sleep(Duration::from_millis(50));
+                    event.notify_reply(String::from("pong"));
+                });
+                assert_eq!(event.wait_for_reply::<String>(), "pong");
+            });
+            assert_eq!(event.try_wait(), !auto_reset);
+        };
+    }
+
+    #[cfg(feature = "registry")]
+    #[test]
+    fn test_dump_all_events() {
+        let manual = Event::new(true, false).unwrap();
+        let auto = Event::new(false, true).unwrap();
+        let row = |event: &Event| {
+            let id = event.registry_id().to_string();
+            dump_all_events().lines().find_map(|line| {
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                if fields[0] == id {
+                    Some((fields[1].to_owned(), fields[2].to_owned()))
+                } else {
+                    None
+                }
+            })
+        };
+        let row_of = |signaled: &str, auto_reset: &str| {
+            Some((signaled.to_owned(), auto_reset.to_owned()))
+        };
+        assert_eq!(row(&manual), row_of("true", "false"));
+        assert_eq!(row(&auto), row_of("false", "true"));
+        auto.notify();
+        assert_eq!(row(&auto), row_of("true", "true"));
+        assert!(auto.try_wait());
+        let id = manual.registry_id().to_string();
+        drop(manual);
+        assert!(!dump_all_events().lines().any(|line| {
+            line.split_whitespace().next() == Some(&id[..])
+        }));
+    }
+
+    #[test]
+    fn test_wait_then_notify() {
+        let stages = (0..4).map(|_| Event::new(false, true).unwrap())
+                           .collect::<Vec<_>>();
+        crossbeam::scope(|scope| {
+            for pair in stages.windows(2) {
+                scope.spawn(move || pair[0].wait_then_notify(&pair[1]));
+            };
+            stages[0].notify();
+            assert!(!stages[3].wait_for(Duration::from_secs(5)).timed_out());
+        });
+        assert!(!stages.iter().any(|stage| stage.try_wait()));
+    }
+
+    #[test]
+    fn test_wait_drawing_budget() {
+        let event = Event::new(false, false).unwrap();
+        let budget = AtomicU64::new(200_000_000);
+        let waited = Mutex::new(Duration::from_secs(0));
+        crossbeam::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let start = Instant::now();
+                    assert!(event.wait_drawing_budget(&budget).timed_out());
+                    *waited.lock().unwrap() += start.elapsed();
+                });
+            };
+        });
+        assert_eq!(budget.load(Ordering::SeqCst), 0);
+        let waited = *waited.lock().unwrap();
+        assert!(waited >= Duration::from_millis(200));
+        assert!(waited < Duration::from_millis(400));
+        assert!(event.wait_drawing_budget(&budget).timed_out());
+
+        budget.store(1_000_000_000, Ordering::SeqCst);
+        event.notify();
+        assert!(!event.wait_drawing_budget(&budget).timed_out());
+        assert!(budget.load(Ordering::SeqCst) > 900_000_000);
+    }
+
+    #[test]
+    fn test_estimated_time_to_next() {
+        let event = EventBuilder::new().track_notify_intervals(true)
+                                       .build()
+                                       .unwrap();
+        assert_eq!(event.estimated_time_to_next(), None);
+        for _ in 0..10 {
+            event.notify();
+            thread::sleep(Duration::from_millis(50));
+        };
+        event.notify();
+        let estimate = event.estimated_time_to_next().unwrap();
+        assert!(estimate > Duration::from_millis(40));
+        assert!(estimate < Duration::from_millis(70));
+        let untracked = Event::new(false, false).unwrap();
+        for _ in 0..10 {
+            untracked.notify();
+        };
+        assert_eq!(untracked.estimated_time_to_next(), None);
+    }
+
+    #[test]
+    fn test_add_child() {
+        let parent = Event::new_parent();
+        let children = (0..3).map(|_| Event::add_child(&parent))
+                             .collect::<Vec<_>>();
+        for &i in &[2, 0, 2] {
+            children[i].notify();
+            assert!(!parent.try_wait());
+        };
+        children[1].notify();
+        assert!(parent.try_wait());
+        children[1].notify();
+        assert_eq!(parent.redundant_notify_count(), 0);
+    }
+
+    #[test]
+    fn test_wait_diagnostic() {
+        let event = Event::new(false, true).unwrap();
+        let diagnostic = event.wait_diagnostic(Some(Duration::from_millis(50)));
+        assert_eq!(diagnostic.outcome, WaitOutcome::TimedOut);
+        assert_eq!(diagnostic.spurious_wakeups, 0);
+        assert!(diagnostic.elapsed >= Duration::from_millis(50));
+        assert!(!diagnostic.fast_path);
+
+        event.notify();
+        let diagnostic = event.wait_diagnostic(None);
+        assert_eq!(diagnostic.outcome, WaitOutcome::Signaled);
+        assert!(diagnostic.fast_path);
+        assert!(!event.try_wait());
+
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                event.notify();
+            });
+            let timeout = Some(Duration::from_secs(5));
+            let diagnostic = event.wait_diagnostic(timeout);
+            assert_eq!(diagnostic.outcome, WaitOutcome::Signaled);
+            assert!(diagnostic.elapsed >= Duration::from_millis(40));
+            assert!(!diagnostic.fast_path);
+        });
+    }
+
+    #[test]
+    fn test_wait_claim() {
+        let event = Event::new(false, true).unwrap();
+        let counter = AtomicUsize::new(0);
+        let claims = AtomicUsize::new(0);
+        crossbeam::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    while event.wait_claim(&counter).is_some() {
+                        claims.fetch_add(1, Ordering::SeqCst);
+                    };
+                });
+            };
+            for _ in 0..100 {
+                counter.fetch_add(1, Ordering::SeqCst);
+                event.notify();
+            };
+            while counter.load(Ordering::SeqCst) > 0 {
+                thread::yield_now();
+            };
+            event.poison();
+        });
+        assert_eq!(claims.load(Ordering::SeqCst), 100);
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace() {
+        let a = Arc::new(Event::new(false, false).unwrap());
+        let b = Arc::new(Event::new(false, false).unwrap());
+        let addresses = [&*a as *const Event as usize,
+                         &*b as *const Event as usize];
+        a.notify();
+        a.wait();
+        thread::sleep(Duration::from_millis(50));
+        b.notify();
+        a.unnotify();
+        // Thread ids are never reused, unlike the addresses of events other
+        // tests have dropped.
+        let trace = export_trace().into_iter().filter(|traced| {
+            traced.thread == thread::current().id() &&
+                addresses.contains(&traced.event)
+        }).collect::<Vec<_>>();
+        let ops = trace.iter().map(|traced| {
+            (traced.op, traced.event == addresses[1])
+        }).collect::<Vec<_>>();
+        assert_eq!(ops, vec![
+            (TraceOp::Notify, false),
+            (TraceOp::Wait, false),
+            (TraceOp::Notify, true),
+            (TraceOp::Unnotify, false),
+        ]);
+        assert!(trace[2].at - trace[1].at >= Duration::from_millis(50));
+
+        let replayed = vec![
+            Arc::new(Event::new(false, false).unwrap()),
+            Arc::new(Event::new(false, false).unwrap()),
+        ];
+        let start = Instant::now();
+        replay(&trace, &replayed);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(!replayed[0].try_wait());
+        assert!(replayed[1].try_wait());
+    }
+
+    #[test]
+    fn test_notify_except() {
+        let event = Event::new(false, true).unwrap();
+        let woken = Mutex::new(Vec::new());
+        crossbeam::scope(|scope| {
+            for key in 1..4 {
+                let event = &event;
+                let woken = &woken;
+                scope.spawn(move || {
+                    event.wait_filtered(key);
+                    woken.lock().unwrap().push(key);
+                });
+            };
+            while woken.lock().unwrap().len() < 2 {
+                event.notify_except(2);
+                thread::sleep(Duration::from_millis(10));
+            };
+            thread::sleep(Duration::from_millis(50));
+            assert_eq!(woken.lock().unwrap().len(), 2);
+            event.notify();
+        });
+        let woken = woken.into_inner().unwrap();
+        assert_eq!(woken[2], 2);
+        assert!(!event.try_wait());
+    }
+
+    // Names the shared event `test_shared_event_child` notifies when run by
+    // `test_shared_event_across_processes` in a child process.
+    #[cfg(target_os = "linux")]
+    const SHARED_EVENT_NAME_VAR: &str = "EVENT_OBJECT_TEST_SHARED_EVENT";
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_shared_event_across_processes() {
+        let name = format!("/event-object-test-{}", process::id());
+        let created = Event::new_shared(&name, false, true).unwrap();
+        assert!(Event::new_shared(&name, false, true).is_err());
+
+        let mut child = Command::new(env::current_exe().unwrap())
+            .args(["tests::test_shared_event_child", "--exact"])
+            .env(SHARED_EVENT_NAME_VAR, &name)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        assert!(!created.wait_for(Duration::from_secs(5)).timed_out());
+        assert!(child.wait().unwrap().success());
+        assert!(created.wait_for(Duration::from_millis(50)).timed_out());
+
+        let opened = Event::open_shared(&name).unwrap();
+        SharedEvent::unlink(&name).unwrap();
+        assert!(Event::open_shared(&name).is_err());
+        opened.notify();
+        assert!(created.try_wait());
+        assert!(!opened.try_wait());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_shared_event_child() {
+        if let Ok(name) = env::var(SHARED_EVENT_NAME_VAR) {
+            thread::sleep(Duration::from_millis(100));
+            Event::open_shared(&name).unwrap().notify();
+        };
+    }
+
+    #[test]
+    fn test_wait_seq() {
+        let events: Vec<_> =
+            (0..3).map(|_| Event::new(false, true).unwrap()).collect();
+        events[1].notify();
+        events[0].notify();
+        events[2].notify();
+        let seqs: Vec<_> =
+            events.iter().map(|event| event.wait_seq()).collect();
+        assert!(seqs[1] < seqs[0]);
+        assert!(seqs[0] < seqs[2]);
+
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let waiter = event.clone();
+        let handle = thread::spawn(move || waiter.wait_seq());
+        thread::sleep(Duration::from_millis(50));
+        event.notify();
+        assert!(handle.join().unwrap() > seqs[2]);
+    }
+
+    #[test]
+    fn test_has_waiters() {
+        let event = Event::new(false, true).unwrap();
+        assert!(!event.has_waiters());
+        crossbeam::scope(|scope| {
+            scope.spawn(|| event.wait());
+            while !event.has_waiters() {
+                thread::yield_now();
+            };
+            event.notify();
+        });
+        assert!(!event.has_waiters());
+
+        let since = event.reset_generation();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| event.wait_for_reset_edge(since));
+            while !event.has_waiters() {
+                thread::yield_now();
+            };
+            event.unnotify();
+        });
+        assert!(!event.has_waiters());
+    }
+
+    #[test]
+    fn test_signal_on_scope_exit() {
+        let success = Event::new(false, false).unwrap();
+        let failure = Event::new(false, false).unwrap();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _signal = Event::signal_on_scope_exit(&success, &failure);
+        }));
+        assert!(result.is_ok());
+        assert!(success.try_wait());
+        assert!(!failure.try_wait());
+
+        success.unnotify();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _signal = Event::signal_on_scope_exit(&success, &failure);
+            panic!("task failed");
+        }));
+        assert!(result.is_err());
+        assert!(!success.try_wait());
+        assert!(failure.try_wait());
+    }
+
+    #[test]
+    fn test_wait_for_high_res() {
+        let event = Event::new(false, false).unwrap();
+        let start = Instant::now();
+        assert!(event.wait_for_high_res(Duration::from_millis(2)).timed_out());
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(2));
+        // Short of the default tick of 15.6ms. Elsewhere this is a plain
+        // `wait_for`, with nothing to check beyond the timeout.
+        #[cfg(windows)]
+        assert!(elapsed < Duration::from_millis(15));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_waiter_capacity() {
+        let event = Arc::new(
+            EventBuilder::new().waiter_capacity(8).build().unwrap()
+        );
+        let capacity = event.waiter_capacity();
+        assert!(capacity >= 8);
+        let event_vec = vec![event.clone()];
+        crossbeam::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| wait_for_any(&event_vec));
+            };
+            thread::sleep(Duration::from_millis(100));
+            assert_eq!(event.waiter_capacity(), capacity);
+            event.notify();
+        });
+        assert_eq!(event.waiter_capacity(), capacity);
+    }
+
+    #[test]
+    fn test_wait_unless() {
+        let event = Event::new(false, true).unwrap();
+        let flag = AtomicBool::new(true);
+        assert!(event.wait_unless(&flag));
+
+        for _ in 0..500 {
+            flag.store(false, Ordering::Relaxed);
+            crossbeam::scope(|scope| {
+                scope.spawn(|| {
+                    flag.store(true, Ordering::Release);
+                    event.notify();
+                });
+                if event.wait_unless(&flag) {
+                    event.wait();
+                };
+            });
+            assert!(!event.try_wait());
+        };
+    }
+
+    #[test]
+    fn test_wait_and_hold() {
+        let gate = Event::new(true, false).unwrap();
+        {
+            let _hold = gate.wait_and_hold();
+            assert!(gate.try_wait());
+        }
+        assert!(!gate.try_wait());
+
+        gate.notify();
+        let first = gate.wait_and_hold();
+        let second = gate.wait_and_hold();
+        drop(first);
+        assert!(gate.try_wait());
+        drop(second);
+        assert!(!gate.try_wait());
+
+        gate.notify();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _hold = gate.wait_and_hold();
+            panic!("work failed");
+        }));
+        assert!(result.is_err());
+        assert!(!gate.try_wait());
+    }
+
+    #[test]
+    fn test_max_wait_duration() {
+        let event = EventBuilder::new().track_max_wait(true).build().unwrap();
+        assert_eq!(event.max_wait_duration(), Duration::from_secs(0));
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(200));
+                event.notify();
+            });
+            event.wait();
+        });
+        assert!(!event.wait_for(Duration::from_millis(10)).timed_out());
+        let max_wait = event.max_wait_duration();
+        assert!(max_wait >= Duration::from_millis(200));
+        assert!(max_wait < Duration::from_millis(400));
+
+        event.reset_max_wait();
+        assert_eq!(event.max_wait_duration(), Duration::from_secs(0));
+        let plain = Event::new(true, false).unwrap();
+        plain.wait();
+        assert_eq!(plain.max_wait_duration(), Duration::from_secs(0));
+    }
+
+    // Every message logged since the first `record_logs`. The hook is
+    // process-wide, so the tests checking the log share this one.
+    static LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn record_log(msg: &str) {
+        LOGGED.lock().unwrap().push(msg.to_owned());
+    }
+
+    pub fn record_logs() {
+        set_log_hook(record_log);
+    }
+
+    // How many times `msg` was logged.
+    pub fn logged(msg: &str) -> usize {
+        LOGGED.lock().unwrap().iter().filter(|logged| *logged == msg).count()
+    }
+
+    #[test]
+    fn test_wait_with_warning() {
+        record_logs();
+
+        let event = Event::new(false, false).unwrap();
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(250));
+                event.notify();
+            });
+            event.wait_with_warning(
+                Duration::from_millis(100),
+                "test_wait_with_warning: still waiting"
+            );
+        });
+        assert_eq!(logged("test_wait_with_warning: still waiting"), 2);
+    }
+
+    #[test]
+    fn test_wait_for_reset_edge() {
+        let event = Event::new(true, false).unwrap();
+        let since_gen = event.reset_generation();
+        crossbeam::scope(|scope| {
+            let edge = scope.spawn(|| event.wait_for_reset_edge(since_gen));
+            thread::sleep(Duration::from_millis(100));
+            event.reset_with_notification();
+            assert_eq!(edge.join(), since_gen + 1);
+        });
+        assert!(!event.try_wait());
+
+        event.notify();
+        event.wait();
+        assert_eq!(event.reset_generation(), since_gen + 1);
+        event.unnotify();
+        assert_eq!(event.wait_for_reset_edge(since_gen + 1), since_gen + 2);
+    }
+
+    #[test]
+    fn test_wait_with_sliding_deadline() {
+        let event = Event::new(false, false).unwrap();
+        let start = Instant::now();
+        let mut refreshes = 0;
+        let outcome = event.wait_with_sliding_deadline(
+            start + Duration::from_millis(100),
+            || {
+                refreshes += 1;
+                if refreshes <= 2 {
+                    Some(Instant::now() + Duration::from_millis(100))
+                } else {
+                    None
+                }
+            }
+        );
+        assert_eq!(outcome, WaitOutcome::TimedOut);
+        assert_eq!(refreshes, 3);
+        assert!(start.elapsed() >= Duration::from_millis(300));
+
+        event.notify();
+        let outcome = event.wait_with_sliding_deadline(
+            Instant::now() + Duration::from_millis(100),
+            || panic!("refreshed a signaled wait")
+        );
+        assert_eq!(outcome, WaitOutcome::Signaled);
+    }
+
+    #[test]
+    fn test_wait_for_or_abort() {
+        let event = Event::new(false, false).unwrap();
+        let start = Instant::now();
+        let mut soft_at = None;
+        let result = event.wait_for_or_abort(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            || soft_at = Some(start.elapsed())
+        );
+        let elapsed = start.elapsed();
+        assert_eq!(result, Err(HardTimeout));
+        let soft_at = soft_at.unwrap();
+        assert!(soft_at >= Duration::from_millis(100));
+        assert!(soft_at < Duration::from_millis(200));
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_millis(400));
+
+        event.notify();
+        let result = event.wait_for_or_abort(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            || panic!("soft timeout on a signaled event")
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_notify_once() {
+        let event = Event::new(true, false).unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        {
+            let calls = calls.clone();
+            event.notify_once(move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        event.notify();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        event.notify();
+        event.unnotify();
+        event.notify();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_callback_panics_are_contained() {
+        let event = Event::new(false, true).unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        event.notify_once(|| panic!("callback"));
+        {
+            let calls = calls.clone();
+            event.notify_once(move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        event.notify();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(!event.wait_for(Duration::from_secs(0)).timed_out());
+
+        let outcome = event.wait_with_sliding_deadline(
+            Instant::now() + Duration::from_millis(10),
+            || panic!("refresh")
+        );
+        assert_eq!(outcome, WaitOutcome::CallbackPanicked);
+        event.notify();
+        assert!(!event.wait_for(Duration::from_secs(0)).timed_out());
+
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let (tx, rx) = mpsc::channel();
+        let sent = Arc::new(AtomicUsize::new(0));
+        let forwarder = {
+            let sent = sent.clone();
+            event.clone().forward_to_sender(tx, move || {
+                if sent.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("make_msg");
+                };
+            })
+        };
+        event.notify();
+        while sent.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        };
+        event.notify();
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+        drop(forwarder);
+    }
+
+    #[test]
+    fn test_duration_to_wait_ms() {
+        assert_eq!(duration_to_wait_ms(Duration::from_secs(0)), 0);
+        assert_eq!(duration_to_wait_ms(Duration::new(0, 1)), 1);
+        assert_eq!(duration_to_wait_ms(Duration::from_micros(999)), 1);
+        assert_eq!(duration_to_wait_ms(Duration::from_millis(1)), 1);
+        assert_eq!(duration_to_wait_ms(Duration::from_micros(1500)), 2);
+        assert_eq!(duration_to_wait_ms(Duration::from_secs(60)), 60_000);
+
+        let longest = Duration::from_millis(u64::from(u32::MAX - 1));
+        assert_eq!(duration_to_wait_ms(longest), u32::MAX - 1);
+        let infinite = Duration::from_millis(u64::from(u32::MAX));
+        assert_eq!(duration_to_wait_ms(infinite), u32::MAX);
+        let too_long = longest + Duration::new(0, 1);
+        assert_eq!(duration_to_wait_ms(too_long), u32::MAX);
+        let max = Duration::from_secs(u64::MAX);
+        assert_eq!(duration_to_wait_ms(max), u32::MAX);
+
+        assert_eq!(wait_ms_to_duration(0), Some(Duration::from_secs(0)));
+        let ms = Duration::from_millis(1500);
+        assert_eq!(wait_ms_to_duration(1500), Some(ms));
+        assert_eq!(wait_ms_to_duration(u32::MAX), None);
+        assert!(WaitMs::INFINITE.is_infinite());
+        assert_eq!(WaitMs::from_duration(infinite), WaitMs::INFINITE);
+        assert_eq!(WaitMs(250).to_duration(), Some(Duration::from_millis(250)));
+
+        let event = Event::new(false, false).unwrap();
+        assert!(event.wait_for_ms(WaitMs(10)).timed_out());
+        event.notify();
+        assert!(!event.wait_for_ms(WaitMs::INFINITE).timed_out());
+    }
+
+    #[test]
+    fn test_weak_watcher() {
+        let event = Arc::new(Event::new(false, false).unwrap());
+        let watcher = event.weak_watcher();
+        let timeout = Duration::from_millis(10);
+        assert!(watcher.try_wait_for(timeout).unwrap().timed_out());
+        event.notify();
+        assert!(!watcher.try_wait_for(timeout).unwrap().timed_out());
+        assert!(watcher.is_alive());
+
+        drop(event);
+        assert!(!watcher.is_alive());
+        assert!(watcher.try_wait_for(Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn test_wait_via_park() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let parked = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let event = event.clone();
+            let parked = parked.clone();
+            thread::spawn(move || {
+                parked.store(true, Ordering::SeqCst);
+                event.wait_via_park();
+            })
+        };
+        event.register_unpark(waiter.thread().clone());
+        while !parked.load(Ordering::SeqCst) {
+            thread::yield_now();
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+        event.notify();
+        waiter.join().unwrap();
+        assert!(!event.try_wait());
+    }
+
+    #[test]
+    fn test_forward_to_sender() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let (tx, rx) = mpsc::channel();
+        let forwarder = event.clone().forward_to_sender(tx.clone(), || "event");
+        tx.send("real").unwrap();
+        event.notify();
+        assert_eq!(rx.recv().unwrap(), "real");
+        assert_eq!(rx.recv().unwrap(), "event");
+        tx.send("real").unwrap();
+        assert_eq!(rx.recv().unwrap(), "real");
+
+        drop(forwarder);
+        event.notify();
+        let timeout = Duration::from_millis(100);
+        assert!(rx.recv_timeout(timeout).is_err());
+    }
+
+    #[test]
+    fn test_wait_coalesced() {
+        let event = Event::new(false, true).unwrap();
+        for _ in 0..5 {
+            event.notify();
+        };
+        assert_eq!(event.wait_coalesced(), 5);
+        assert!(!event.try_wait());
+
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                event.notify();
+            });
+            assert_eq!(event.wait_coalesced(), 1);
+        });
+    }
+
+    #[test]
+    fn test_ping_pong_pair() {
+        let (a, b) = Event::ping_pong_pair();
+        let turns = Mutex::new(Vec::new());
+        crossbeam::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..5 {
+                    b.wait_pong();
+                    turns.lock().unwrap().push('b');
+                    b.ping();
+                };
+            });
+            for _ in 0..5 {
+                turns.lock().unwrap().push('a');
+                a.ping();
+                a.wait_pong();
+            };
+        });
+        let turns = turns.into_inner().unwrap();
+        assert_eq!(turns.iter().collect::<String>(), "ababababab");
+    }
+
+    #[test]
+    fn test_timed_event() {
+        let event = Arc::new(Event::new(false, true).unwrap());
+        let timed = event.clone().with_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+        assert!(timed.wait().timed_out());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        event.notify();
+        assert!(!timed.wait().timed_out());
+
+        let timed = event.clone()
+            .with_deadline(Deadline::after(Duration::from_millis(100)));
+        assert!(timed.wait().timed_out());
+        let start = Instant::now();
+        assert!(timed.wait().timed_out());
+        assert!(start.elapsed() < Duration::from_millis(50));
+        assert!(Arc::ptr_eq(timed.event(), &event));
+    }
+
+    #[test]
+    fn test_notify_robust() {
+        let event = Event::new(false, false).unwrap();
+        assert!(event.notify_robust(3, Duration::from_millis(1)).is_ok());
+        assert!(!event.wait_for(Duration::from_secs(0)).timed_out());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_notify_robust_retries() {
+        use std::io;
+
+        let event = Event::new(false, false).unwrap();
+        let backoff = Duration::from_millis(1);
+        let mut failures = 2;
+        let result = event.notify_with(2, backoff, |event| {
+            if failures == 0 {
+                return event.set_event();
+            };
+            failures -= 1;
+            Err(io::Error::from_raw_os_error(8))
+        });
+        assert!(result.is_ok());
+        assert!(!event.wait_for(Duration::from_secs(0)).timed_out());
+
+        event.unnotify();
+        let result = event.notify_with(2, backoff, |_| {
+            Err(io::Error::from_raw_os_error(8))
+        });
+        assert_eq!(result.unwrap_err().raw_os_error(), Some(8));
+        assert!(event.wait_for(Duration::from_secs(0)).timed_out());
+    }
+
+    #[test]
+    fn test_last_waiter_location() {
+        let event = EventBuilder::new()
+            .initial_signaled(true)
+            .track_waiter_locations(true)
+            .build()
+            .unwrap();
+        assert!(event.last_waiter_location().is_none());
+        let line = line!() + 1;
+        event.wait();
+        let location = event.last_waiter_location().unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line);
+
+        let line = line!() + 1;
+        event.wait_for_ms(WaitMs(10));
+        assert_eq!(event.last_waiter_location().unwrap().line(), line);
+
+        let untracked = Event::new(true, false).unwrap();
+        untracked.wait();
+        assert!(untracked.last_waiter_location().is_none());
+    }
+
+    #[test]
+    fn test_bounded_event_throttles_notifier() {
+        let event = Arc::new(BoundedEvent::new(2));
+        let notified = Arc::new(AtomicUsize::new(0));
+        let notifier = {
+            let event = event.clone();
+            let notified = notified.clone();
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    event.notify();
+                    notified.fetch_add(1, Ordering::SeqCst);
+                };
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(notified.load(Ordering::SeqCst), 2);
+        assert_eq!(event.pending(), 2);
+        for consumed in 1..6 {
+            event.wait();
+            thread::sleep(Duration::from_millis(50));
+            assert_eq!(notified.load(Ordering::SeqCst), (consumed + 2).min(5));
+        };
+        notifier.join().unwrap();
+        assert_eq!(event.pending(), 0);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_try_wait_too_many_objects() {
+        let mut event_vec = vec![];
+        for _ in 0..65 {
+            event_vec.push(Arc::new(Event::new(true, false).unwrap()));
+        };
+        assert_eq!(
+            try_wait_for_any(&event_vec),
+            Err(WaitError::TooManyObjects)
+        );
+    }
+
     #[test]
     fn test_wait_for_all_with() {
         let mut event_vec = vec![];
@@ -98,4 +1552,43 @@ mod tests {
         let result = wait_for_all_with(&event_vec, Duration::from_millis(200));
         assert!(result.timed_out());
     }
+
+    #[test]
+    fn test_delayed_notifier() {
+        let notifier = DelayedNotifier::new();
+        let mut event_vec = vec![];
+        for _ in 0..3 {
+            event_vec.push(Arc::new(Event::new(false, false).unwrap()));
+        };
+        let start = Instant::now();
+        notifier.notify_after(event_vec[0].clone(), Duration::from_millis(300));
+        notifier.notify_after(event_vec[1].clone(), Duration::from_millis(100));
+        notifier.notify_after(event_vec[2].clone(), Duration::from_millis(200));
+        for &(i, ms) in &[(1, 100), (2, 200), (0, 300)] {
+            event_vec[i].wait();
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(ms));
+            assert!(elapsed < Duration::from_millis(ms + 100));
+        };
+    }
+
+    #[test]
+    fn test_thread_name_prefix() {
+        // The prefix is process-wide; put the default back for the other
+        // tests, also on failure.
+        struct RestorePrefix;
+        impl Drop for RestorePrefix {
+            fn drop(&mut self) {
+                set_thread_name_prefix("event-object");
+            }
+        }
+
+        let thread_name = || thread::current().name().map(String::from);
+        let name = spawn_named("test", thread_name).join().unwrap();
+        assert_eq!(name.as_ref().map(|s| &s[..]), Some("event-object:test"));
+        let _restore = RestorePrefix;
+        set_thread_name_prefix("custom");
+        let name = spawn_named("test", thread_name).join().unwrap();
+        assert_eq!(name.as_ref().map(|s| &s[..]), Some("custom:test"));
+    }
 }
@@ -0,0 +1,43 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::Event;
+
+static HOOK: Mutex<Option<fn(&str)>> = Mutex::new(None);
+
+/// Routes the crate's diagnostic messages to `hook`, e.g. to forward them to
+/// `log` or `tracing`. The crate is silent until a hook is set.
+pub fn set_log_hook(hook: fn(&str)) {
+    *HOOK.lock().unwrap() = Some(hook);
+}
+
+pub fn log(msg: &str) {
+    let hook = *HOOK.lock().unwrap();
+    if let Some(hook) = hook {
+        hook(msg);
+    };
+}
+
+// Runs a user callback whose panic must not unwind into the crate's own
+// machinery, logging the panic as `<what> panicked` instead.
+pub fn catch_callback_panic<F: FnOnce() -> R, R>(what: &str, f: F) ->
+    Option<R>
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    if result.is_err() {
+        log(&format!("{} panicked", what));
+    };
+    result.ok()
+}
+
+impl Event {
+    /// Waits for the event without a timeout, logging `msg` through the
+    /// hook set with `set_log_hook` each time another `warn_after` passes
+    /// without a signal.
+    pub fn wait_with_warning(&self, warn_after: Duration, msg: &str) {
+        while self.wait_for(warn_after).timed_out() {
+            log(msg);
+        };
+    }
+}
@@ -0,0 +1,110 @@
+extern crate parking_lot;
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use self::parking_lot::{Condvar, Mutex};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParkResult {
+    Unparked,
+    TimedOut,
+}
+
+/// The blocking primitive behind the single-event waits of an event, such
+/// as `wait`, `wait_for` or `wait_consuming`. Events use a `CondvarParker`
+/// unless built with `Event::with_parker`, for tests that need to drive
+/// waits deterministically and platforms with their own way to block.
+///
+/// An event shares one parker between all of its waiters and, when it is
+/// signaled, calls `unpark` once per parked waiter that an earlier `unpark`
+/// isn't already waking. An `unpark` must not be lost when it comes before
+/// the matching `park`: like a semaphore permit, it makes the next `park`
+/// return at once. `park` may also return spuriously; the event checks its
+/// state again either way.
+pub trait Parker: Send + Sync {
+    /// Blocks until unparked or, if given, until `deadline`.
+    fn park(&self, deadline: Option<Instant>) -> ParkResult;
+
+    fn unpark(&self);
+}
+
+/// The default `Parker`, counting permits under a parking_lot mutex and
+/// condvar.
+#[derive(Default)]
+pub struct CondvarParker {
+    permits: Mutex<usize>,
+    condvar: Condvar,
+}
+
+impl CondvarParker {
+    pub fn new() -> Self {
+        CondvarParker::default()
+    }
+}
+
+impl Parker for CondvarParker {
+    fn park(&self, deadline: Option<Instant>) -> ParkResult {
+        let mut permits = self.permits.lock();
+        while *permits == 0 {
+            match deadline {
+                Some(deadline) => {
+                    let result =
+                        self.condvar.wait_until(&mut permits, deadline);
+                    if result.timed_out() && *permits == 0 {
+                        return ParkResult::TimedOut;
+                    };
+                },
+                None => self.condvar.wait(&mut permits),
+            };
+        };
+        *permits -= 1;
+        ParkResult::Unparked
+    }
+
+    fn unpark(&self) {
+        *self.permits.lock() += 1;
+        self.condvar.notify_one();
+    }
+}
+
+// Parks on the primitives of an event built with `Event::from_parts`, so
+// that external code notifying the condvar still wakes plain waits. The
+// state is checked under the mutex before blocking, which keeps an unpark
+// coming first from being lost.
+pub(crate) struct SharedCondvarParker {
+    mutex: Arc<Mutex<bool>>,
+    condvar: Arc<Condvar>,
+}
+
+impl SharedCondvarParker {
+    pub(crate) fn new(mutex: Arc<Mutex<bool>>, condvar: Arc<Condvar>) -> Self {
+        SharedCondvarParker {
+            mutex: mutex,
+            condvar: condvar,
+        }
+    }
+}
+
+impl Parker for SharedCondvarParker {
+    fn park(&self, deadline: Option<Instant>) -> ParkResult {
+        let mut signaled = self.mutex.lock();
+        if *signaled {
+            return ParkResult::Unparked;
+        };
+        match deadline {
+            Some(deadline) => {
+                let result = self.condvar.wait_until(&mut signaled, deadline);
+                if result.timed_out() {
+                    return ParkResult::TimedOut;
+                };
+            },
+            None => self.condvar.wait(&mut signaled),
+        };
+        ParkResult::Unparked
+    }
+
+    fn unpark(&self) {
+        self.condvar.notify_all();
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use super::Event;
+
+/// One end of a two-party signal channel created by `Event::ping_pong_pair`.
+///
+/// Each end owns an auto-reset event: `ping` signals the other end's event,
+/// `wait_pong` waits on its own. Meant for exactly two threads taking turns;
+/// with more parties, who receives a ping is unspecified.
+pub struct PingPong {
+    own: Arc<Event>,
+    other: Arc<Event>,
+}
+
+impl Event {
+    pub fn ping_pong_pair() -> (PingPong, PingPong) {
+        let first = Arc::new(Event::new(false, true).unwrap());
+        let second = Arc::new(Event::new(false, true).unwrap());
+        (
+            PingPong { own: first.clone(), other: second.clone() },
+            PingPong { own: second, other: first },
+        )
+    }
+}
+
+impl PingPong {
+    pub fn ping(&self) {
+        self.other.notify();
+    }
+
+    pub fn wait_pong(&self) {
+        self.own.wait();
+    }
+}
@@ -0,0 +1,42 @@
+use std::marker::PhantomData;
+
+use super::Event;
+
+/// Proof that an event was signaled, returned by `Event::wait_proof`.
+///
+/// It has no public constructor, so code that takes a `&Signaled` can only
+/// be called once a wait has returned. The token borrows the event and
+/// cannot outlive it, but it does not say which event it came from.
+///
+/// ```
+/// use event_object::{Event, Signaled};
+///
+/// fn serve(_started: &Signaled) {}
+///
+/// let started = Event::new(true, false).unwrap();
+/// let proof = started.wait_proof();
+/// serve(&proof);
+/// ```
+///
+/// Skipping the wait does not compile:
+///
+/// ```compile_fail
+/// use event_object::Signaled;
+///
+/// fn serve(_started: &Signaled) {}
+///
+/// serve(&Signaled { _event: Default::default() });
+/// ```
+#[derive(Debug)]
+pub struct Signaled<'a> {
+    _event: PhantomData<&'a Event>,
+}
+
+impl Event {
+    /// Waits like `wait` and returns a `Signaled` token as proof.
+    #[track_caller]
+    pub fn wait_proof(&self) -> Signaled<'_> {
+        self.wait();
+        Signaled { _event: PhantomData }
+    }
+}
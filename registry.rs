@@ -0,0 +1,91 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use super::Event;
+
+type Probe = Box<dyn Fn() -> bool + Send>;
+
+// What `dump_all_events` reports about one event. Owned by the event; the
+// registry only keeps a weak reference.
+pub struct RegistryEntry {
+    id: u64,
+    auto_reset: bool,
+    // Reads the signaled state without consuming it. Cleared when the event
+    // is dropped, before its primitives are.
+    probe: Mutex<Option<Probe>>,
+    waiters: AtomicUsize,
+    changed_at: Mutex<Instant>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+static ENTRIES: Mutex<Vec<Weak<RegistryEntry>>> = Mutex::new(Vec::new());
+
+impl RegistryEntry {
+    pub fn register(auto_reset: bool, probe: Probe) -> Arc<Self> {
+        let entry = Arc::new(RegistryEntry {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            auto_reset: auto_reset,
+            probe: Mutex::new(Some(probe)),
+            waiters: AtomicUsize::new(0),
+            changed_at: Mutex::new(Instant::now()),
+        });
+        ENTRIES.lock().unwrap().push(Arc::downgrade(&entry));
+        entry
+    }
+
+    pub fn add_waiter(&self) {
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn remove_waiter(&self) {
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // Called on every notify and explicit reset.
+    pub fn state_changed(&self) {
+        *self.changed_at.lock().unwrap() = Instant::now();
+    }
+
+    pub fn retire(&self) {
+        *self.probe.lock().unwrap() = None;
+    }
+}
+
+impl Event {
+    /// The id under which `dump_all_events` lists the event.
+    pub fn registry_id(&self) -> u64 {
+        self.registry_entry().id
+    }
+}
+
+/// Returns a table of every live event, one per line: its registry id,
+/// whether it is signaled, whether it is auto-reset, how many threads are
+/// blocked on it, and how long ago it was last notified or reset (or
+/// created). Dead entries are pruned on the way.
+pub fn dump_all_events() -> String {
+    let mut entries = ENTRIES.lock().unwrap();
+    entries.retain(|entry| entry.strong_count() > 0);
+    let mut dump = format!(
+        "{:>8} {:>8} {:>10} {:>7}  in state\n",
+        "id", "signaled", "auto_reset", "waiters"
+    );
+    for entry in entries.iter().filter_map(Weak::upgrade) {
+        let signaled = match *entry.probe.lock().unwrap() {
+            Some(ref probe) => probe(),
+            None => continue,
+        };
+        let _ = writeln!(
+            dump,
+            "{:>8} {:>8} {:>10} {:>7}  {:?}",
+            entry.id,
+            signaled,
+            entry.auto_reset,
+            entry.waiters.load(Ordering::Relaxed),
+            entry.changed_at.lock().unwrap().elapsed()
+        );
+    };
+    dump
+}
@@ -0,0 +1,35 @@
+use std::thread;
+
+use super::Event;
+
+/// Signals one of two events when dropped: `failure` if the thread is
+/// unwinding from a panic, `success` otherwise. Created by
+/// `Event::signal_on_scope_exit`.
+#[must_use]
+pub struct ScopeSignal<'a> {
+    success: &'a Event,
+    failure: &'a Event,
+}
+
+impl Event {
+    /// Returns a guard reporting how the enclosing scope was left: it
+    /// notifies `success` on a normal exit and `failure` during a panic.
+    pub fn signal_on_scope_exit<'a>(success: &'a Event, failure: &'a Event) ->
+        ScopeSignal<'a>
+    {
+        ScopeSignal {
+            success: success,
+            failure: failure,
+        }
+    }
+}
+
+impl<'a> Drop for ScopeSignal<'a> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.failure.notify();
+        } else {
+            self.success.notify();
+        };
+    }
+}
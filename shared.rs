@@ -0,0 +1,343 @@
+extern crate libc;
+
+use std::ffi::CString;
+use std::io;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Event, EventError, WaitTimeoutResult};
+
+// Written last by the creator, once the mutex and condvar are usable.
+const READY: u32 = 0x4556_4f42;
+
+// How long `open_shared` waits for a creator that is still initializing the
+// region.
+const OPEN_TIMEOUT_MS: u64 = 1000;
+
+// The layout of the shared memory region. Other processes use it at the same
+// time, so it is only ever accessed through raw pointers; `signaled` is only
+// touched with the mutex held.
+#[repr(C)]
+struct Shared {
+    mutex: libc::pthread_mutex_t,
+    condvar: libc::pthread_cond_t,
+    signaled: AtomicBool,
+    auto_reset: AtomicBool,
+    ready: AtomicU32,
+}
+
+/// An event living in POSIX shared memory, created by `Event::new_shared`
+/// and opened by name from any process with `Event::open_shared`.
+///
+/// The region outlives every handle: it stays in place, keeping its state,
+/// until `SharedEvent::unlink` removes the name and the last process unmaps
+/// it. Dropping a handle only unmaps it.
+///
+/// The mutex is robust: if a process dies while holding it, the next locker
+/// takes it over instead of blocking forever. A process killed in the middle
+/// of `notify` or `wait` cannot corrupt the state, but a signal it was
+/// meant to deliver is lost.
+///
+/// Linux only: robust process-shared mutexes and condvars timed on
+/// `CLOCK_MONOTONIC` are missing from other Unix systems, such as macOS.
+pub struct SharedEvent {
+    shared: *mut Shared,
+}
+
+unsafe impl Send for SharedEvent {}
+unsafe impl Sync for SharedEvent {}
+
+impl Event {
+    /// Creates the shared event `name`, a POSIX shared memory name such as
+    /// `/my-event`. Fails if it already exists.
+    pub fn new_shared(name: &str, initial_signaled: bool, auto_reset: bool) ->
+        Result<SharedEvent, EventError>
+    {
+        let fd = shm_open(name, libc::O_CREAT | libc::O_EXCL | libc::O_RDWR)?;
+        let size = mem::size_of::<Shared>() as libc::off_t;
+        if unsafe { libc::ftruncate(fd, size) } != 0 {
+            let error = last_error();
+            unsafe {
+                libc::close(fd);
+                libc::shm_unlink(shm_name(name)?.as_ptr());
+            };
+            return Err(error);
+        };
+        let event = match map(fd) {
+            Ok(shared) => SharedEvent { shared: shared },
+            Err(error) => {
+                unsafe { libc::shm_unlink(shm_name(name)?.as_ptr()) };
+                return Err(error);
+            },
+        };
+        let initialized = unsafe {
+            init_mutex(event.mutex())
+                .and_then(|_| init_condvar(event.condvar()))
+        };
+        if let Err(error) = initialized {
+            drop(event);
+            unsafe { libc::shm_unlink(shm_name(name)?.as_ptr()) };
+            return Err(error);
+        };
+        event.signaled().store(initial_signaled, Ordering::Relaxed);
+        event.auto_reset().store(auto_reset, Ordering::Relaxed);
+        event.ready().store(READY, Ordering::Release);
+        Ok(event)
+    }
+
+    /// Opens the shared event `name` created by `new_shared`, waiting
+    /// briefly for a creator that is still setting it up.
+    pub fn open_shared(name: &str) -> Result<SharedEvent, EventError> {
+        let fd = shm_open(name, libc::O_RDWR)?;
+        let deadline = Instant::now() + Duration::from_millis(OPEN_TIMEOUT_MS);
+        while file_size(fd)? < mem::size_of::<Shared>() {
+            if Instant::now() > deadline {
+                unsafe { libc::close(fd) };
+                return Err(not_ready());
+            };
+            thread::yield_now();
+        };
+        let event = SharedEvent { shared: map(fd)? };
+        while event.ready().load(Ordering::Acquire) != READY {
+            if Instant::now() > deadline {
+                return Err(not_ready());
+            };
+            thread::yield_now();
+        };
+        Ok(event)
+    }
+}
+
+impl SharedEvent {
+    /// Removes the name `name`, so that it can no longer be opened. Handles
+    /// already open keep working; the memory is freed once they are all
+    /// dropped.
+    pub fn unlink(name: &str) -> Result<(), EventError> {
+        if unsafe { libc::shm_unlink(shm_name(name)?.as_ptr()) } != 0 {
+            return Err(last_error());
+        };
+        Ok(())
+    }
+
+    pub fn notify(&self) {
+        self.lock();
+        self.signaled().store(true, Ordering::Relaxed);
+        unsafe { libc::pthread_cond_broadcast(self.condvar()) };
+        self.unlock();
+    }
+
+    pub fn unnotify(&self) {
+        self.lock();
+        self.signaled().store(false, Ordering::Relaxed);
+        self.unlock();
+    }
+
+    pub fn try_wait(&self) -> bool {
+        self.lock();
+        let signaled = self.signaled().load(Ordering::Relaxed);
+        self.consume();
+        self.unlock();
+        signaled
+    }
+
+    pub fn wait(&self) {
+        self.lock();
+        while !self.signaled().load(Ordering::Relaxed) {
+            let result = unsafe {
+                libc::pthread_cond_wait(self.condvar(), self.mutex())
+            };
+            self.recover(result);
+        };
+        self.consume();
+        self.unlock();
+    }
+
+    pub fn wait_for(&self, timeout: Duration) -> WaitTimeoutResult {
+        let deadline = monotonic_deadline(timeout);
+        self.lock();
+        let mut timed_out = false;
+        while !self.signaled().load(Ordering::Relaxed) && !timed_out {
+            let result = unsafe {
+                libc::pthread_cond_timedwait(
+                    self.condvar(),
+                    self.mutex(),
+                    &deadline
+                )
+            };
+            self.recover(result);
+            timed_out = result == libc::ETIMEDOUT;
+        };
+        timed_out = !self.signaled().load(Ordering::Relaxed);
+        self.consume();
+        self.unlock();
+        WaitTimeoutResult::new(timed_out)
+    }
+
+    pub fn wait_until(&self, timeout: Instant) -> WaitTimeoutResult {
+        self.wait_for(timeout.saturating_duration_since(Instant::now()))
+    }
+
+    fn mutex(&self) -> *mut libc::pthread_mutex_t {
+        unsafe { ptr::addr_of_mut!((*self.shared).mutex) }
+    }
+
+    fn condvar(&self) -> *mut libc::pthread_cond_t {
+        unsafe { ptr::addr_of_mut!((*self.shared).condvar) }
+    }
+
+    fn signaled(&self) -> &AtomicBool {
+        unsafe { &*ptr::addr_of!((*self.shared).signaled) }
+    }
+
+    fn auto_reset(&self) -> &AtomicBool {
+        unsafe { &*ptr::addr_of!((*self.shared).auto_reset) }
+    }
+
+    fn ready(&self) -> &AtomicU32 {
+        unsafe { &*ptr::addr_of!((*self.shared).ready) }
+    }
+
+    // Resets a signaled auto-reset event. Called with the mutex held.
+    fn consume(&self) {
+        if self.auto_reset().load(Ordering::Relaxed) {
+            self.signaled().store(false, Ordering::Relaxed);
+        };
+    }
+
+    // Takes the mutex over from a process that died holding it.
+    fn recover(&self, result: libc::c_int) {
+        if result == libc::EOWNERDEAD {
+            unsafe { libc::pthread_mutex_consistent(self.mutex()) };
+        };
+    }
+
+    fn lock(&self) {
+        let result = unsafe { libc::pthread_mutex_lock(self.mutex()) };
+        self.recover(result);
+    }
+
+    fn unlock(&self) {
+        unsafe { libc::pthread_mutex_unlock(self.mutex()) };
+    }
+}
+
+impl Drop for SharedEvent {
+    fn drop(&mut self) {
+        let size = mem::size_of::<Shared>();
+        unsafe { libc::munmap(self.shared as *mut libc::c_void, size) };
+    }
+}
+
+fn last_error() -> EventError {
+    EventError::Os(io::Error::last_os_error())
+}
+
+fn not_ready() -> EventError {
+    EventError::Os(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "shared event was not initialized in time"
+    ))
+}
+
+fn shm_name(name: &str) -> Result<CString, EventError> {
+    CString::new(name).map_err(|error| {
+        EventError::Os(io::Error::new(io::ErrorKind::InvalidInput, error))
+    })
+}
+
+fn shm_open(name: &str, flags: libc::c_int) -> Result<libc::c_int, EventError> {
+    let name = shm_name(name)?;
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600) };
+    if fd < 0 {
+        return Err(last_error());
+    };
+    Ok(fd)
+}
+
+fn file_size(fd: libc::c_int) -> Result<usize, EventError> {
+    let mut stat = MaybeUninit::<libc::stat>::uninit();
+    if unsafe { libc::fstat(fd, stat.as_mut_ptr()) } != 0 {
+        let error = last_error();
+        unsafe { libc::close(fd) };
+        return Err(error);
+    };
+    Ok(unsafe { stat.assume_init() }.st_size as usize)
+}
+
+// Maps the region and closes `fd`, which the mapping does not need.
+fn map(fd: libc::c_int) -> Result<*mut Shared, EventError> {
+    let shared = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            mem::size_of::<Shared>(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0
+        )
+    };
+    let error = last_error();
+    unsafe { libc::close(fd) };
+    if shared == libc::MAP_FAILED {
+        return Err(error);
+    };
+    Ok(shared as *mut Shared)
+}
+
+// The pthread calls return the error number instead of setting `errno`.
+fn check(result: libc::c_int) -> Result<(), EventError> {
+    if result != 0 {
+        return Err(EventError::Os(io::Error::from_raw_os_error(result)));
+    };
+    Ok(())
+}
+
+unsafe fn init_mutex(mutex: *mut libc::pthread_mutex_t) ->
+    Result<(), EventError>
+{
+    let mut attr = MaybeUninit::<libc::pthread_mutexattr_t>::uninit();
+    check(libc::pthread_mutexattr_init(attr.as_mut_ptr()))?;
+    let result = check(libc::pthread_mutexattr_setpshared(
+        attr.as_mut_ptr(),
+        libc::PTHREAD_PROCESS_SHARED
+    )).and_then(|_| check(libc::pthread_mutexattr_setrobust(
+        attr.as_mut_ptr(),
+        libc::PTHREAD_MUTEX_ROBUST
+    ))).and_then(|_| check(libc::pthread_mutex_init(mutex, attr.as_ptr())));
+    libc::pthread_mutexattr_destroy(attr.as_mut_ptr());
+    result
+}
+
+unsafe fn init_condvar(condvar: *mut libc::pthread_cond_t) ->
+    Result<(), EventError>
+{
+    let mut attr = MaybeUninit::<libc::pthread_condattr_t>::uninit();
+    check(libc::pthread_condattr_init(attr.as_mut_ptr()))?;
+    let result = check(libc::pthread_condattr_setpshared(
+        attr.as_mut_ptr(),
+        libc::PTHREAD_PROCESS_SHARED
+    )).and_then(|_| check(libc::pthread_condattr_setclock(
+        attr.as_mut_ptr(),
+        libc::CLOCK_MONOTONIC
+    ))).and_then(|_| check(libc::pthread_cond_init(condvar, attr.as_ptr())));
+    libc::pthread_condattr_destroy(attr.as_mut_ptr());
+    result
+}
+
+// The absolute `CLOCK_MONOTONIC` time `timeout` from now, saturating far in
+// the future.
+fn monotonic_deadline(timeout: Duration) -> libc::timespec {
+    let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+    let nanos = now.tv_nsec + timeout.subsec_nanos() as libc::c_long;
+    let secs = timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t;
+    libc::timespec {
+        tv_sec: now.tv_sec
+            .saturating_add(secs)
+            .saturating_add((nanos / 1_000_000_000) as libc::time_t),
+        tv_nsec: nanos % 1_000_000_000,
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// The live counters behind `EventStats`, updated by the wait and notify
+// paths of an event built with stats enabled.
+#[derive(Debug, Default)]
+pub struct StatsCounters {
+    waits: AtomicU64,
+    notifies: AtomicU64,
+    timeouts: AtomicU64,
+    spurious_wakeups: AtomicU64,
+    wait_nanos: AtomicU64,
+}
+
+impl StatsCounters {
+    pub fn record_wait(&self, start: Instant, timed_out: bool) {
+        let nanos = elapsed_nanos(start);
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        self.wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+        if timed_out {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+        };
+    }
+
+    pub fn record_notify(&self) {
+        self.notifies.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(not(windows))]
+    pub fn record_spurious(&self) {
+        self.spurious_wakeups.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// The process-wide notify counter behind `Event::wait_seq`. Starts at 1 so
+// that 0 means an event was never notified.
+static NOTIFY_SEQ: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_notify_seq() -> u64 {
+    NOTIFY_SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+// How many intervals `NotifyIntervals` needs before it gives an estimate,
+// and how much weight, as a power of two, each new interval gets in the
+// moving average (1/8).
+const MIN_INTERVALS: u32 = 3;
+const INTERVAL_WEIGHT_SHIFT: u32 = 3;
+
+// Moving average of the time between notifies, behind
+// `Event::estimated_time_to_next`.
+#[derive(Debug, Default)]
+pub struct NotifyIntervals {
+    state: Mutex<IntervalState>,
+}
+
+#[derive(Debug, Default)]
+struct IntervalState {
+    last: Option<Instant>,
+    mean_nanos: u64,
+    intervals: u32,
+}
+
+impl NotifyIntervals {
+    pub fn record_notify(&self) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if let Some(last) = state.last {
+            let interval = elapsed_nanos_between(last, now);
+            state.mean_nanos = if state.intervals == 0 {
+                interval
+            } else {
+                let mean = state.mean_nanos as i128;
+                let delta = (interval as i128 - mean) >> INTERVAL_WEIGHT_SHIFT;
+                (mean + delta) as u64
+            };
+            state.intervals = state.intervals.saturating_add(1);
+        };
+        state.last = Some(now);
+    }
+
+    pub fn estimated_time_to_next(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        if state.intervals < MIN_INTERVALS {
+            return None;
+        };
+        let since_last = elapsed_nanos(state.last?);
+        Some(Duration::from_nanos(state.mean_nanos.saturating_sub(since_last)))
+    }
+}
+
+fn elapsed_nanos_between(start: Instant, end: Instant) -> u64 {
+    let elapsed = end - start;
+    elapsed.as_secs() * 1_000_000_000 + u64::from(elapsed.subsec_nanos())
+}
+
+pub fn elapsed_nanos(start: Instant) -> u64 {
+    elapsed_nanos_between(start, Instant::now())
+}
+
+/// Aggregate wait and notify counters of an event, returned by
+/// `Event::stats`.
+///
+/// Only events built with `EventBuilder::stats(true)` keep counters; for any
+/// other event every counter reads zero. Clones share the same live
+/// counters, so a retained `EventStats` keeps reflecting new activity.
+#[derive(Debug, Clone)]
+pub struct EventStats {
+    counters: Arc<StatsCounters>,
+}
+
+impl EventStats {
+    pub(crate) fn new(counters: Arc<StatsCounters>) -> Self {
+        EventStats { counters: counters }
+    }
+
+    /// Completed `wait`, `wait_for` and `wait_until` calls (and their `try_`
+    /// variants), timed out or not.
+    pub fn waits(&self) -> u64 {
+        self.counters.waits.load(Ordering::Relaxed)
+    }
+
+    pub fn notifies(&self) -> u64 {
+        self.counters.notifies.load(Ordering::Relaxed)
+    }
+
+    pub fn timeouts(&self) -> u64 {
+        self.counters.timeouts.load(Ordering::Relaxed)
+    }
+
+    /// Wakeups that found the event still unsignaled. Always zero on
+    /// Windows, where the kernel does not wake waiters spuriously.
+    pub fn spurious_wakeups(&self) -> u64 {
+        self.counters.spurious_wakeups.load(Ordering::Relaxed)
+    }
+
+    /// Total time spent blocked in the counted waits.
+    pub fn wait_time(&self) -> Duration {
+        Duration::from_nanos(self.counters.wait_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Sets every counter of the event back to zero.
+    pub fn reset(&self) {
+        self.counters.waits.store(0, Ordering::Relaxed);
+        self.counters.notifies.store(0, Ordering::Relaxed);
+        self.counters.timeouts.store(0, Ordering::Relaxed);
+        self.counters.spurious_wakeups.store(0, Ordering::Relaxed);
+        self.counters.wait_nanos.store(0, Ordering::Relaxed);
+    }
+}
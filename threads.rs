@@ -0,0 +1,25 @@
+use std::sync::Mutex;
+use std::thread::{Builder, JoinHandle};
+
+const DEFAULT_PREFIX: &str = "event-object";
+
+static PREFIX: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the prefix of the names given to the crate's background threads,
+/// `event-object` by default. Threads already running keep their name.
+pub fn set_thread_name_prefix(prefix: &str) {
+    *PREFIX.lock().unwrap() = Some(prefix.to_owned());
+}
+
+// Every background thread of the crate is spawned through here, named
+// `<prefix>:<name>`.
+pub fn spawn_named<F, T>(name: &str, f: F) -> JoinHandle<T>
+    where F: FnOnce() -> T + Send + 'static,
+          T: Send + 'static
+{
+    let full_name = match *PREFIX.lock().unwrap() {
+        Some(ref prefix) => format!("{}:{}", prefix, name),
+        None => format!("{}:{}", DEFAULT_PREFIX, name),
+    };
+    Builder::new().name(full_name).spawn(f).expect("failed to spawn thread")
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{Deadline, Event, WaitTimeoutResult};
+
+/// An event bundled with the limit on how long to wait for it, created by
+/// `Event::with_timeout` or `Event::with_deadline`, so that the limit is
+/// chosen where the handle is made rather than where it is waited on.
+#[derive(Clone)]
+pub struct TimedEvent {
+    event: Arc<Event>,
+    limit: Limit,
+}
+
+#[derive(Copy, Clone)]
+enum Limit {
+    // Applied afresh by every wait.
+    Timeout(Duration),
+    // Shared by every wait.
+    Deadline(Deadline),
+}
+
+impl Event {
+    pub fn with_timeout(self: Arc<Self>, timeout: Duration) -> TimedEvent {
+        TimedEvent {
+            event: self,
+            limit: Limit::Timeout(timeout),
+        }
+    }
+
+    /// Like `with_timeout`, but every wait ends at `deadline`, so waits
+    /// started later get less time.
+    pub fn with_deadline(self: Arc<Self>, deadline: Deadline) -> TimedEvent {
+        TimedEvent {
+            event: self,
+            limit: Limit::Deadline(deadline),
+        }
+    }
+}
+
+impl TimedEvent {
+    pub fn wait(&self) -> WaitTimeoutResult {
+        match self.limit {
+            Limit::Timeout(timeout) => self.event.wait_for(timeout),
+            Limit::Deadline(ref deadline) => self.event.wait_within(deadline),
+        }
+    }
+
+    pub fn event(&self) -> &Arc<Event> {
+        &self.event
+    }
+}
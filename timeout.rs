@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+/// A wait timeout in whole milliseconds, the unit of the Windows wait
+/// functions. `WaitMs::INFINITE` means no timeout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WaitMs(pub u32);
+
+impl WaitMs {
+    pub const INFINITE: WaitMs = WaitMs(u32::MAX);
+
+    pub fn from_duration(duration: Duration) -> Self {
+        WaitMs(duration_to_wait_ms(duration))
+    }
+
+    /// `None` for `WaitMs::INFINITE`.
+    pub fn to_duration(self) -> Option<Duration> {
+        wait_ms_to_duration(self.0)
+    }
+
+    pub fn is_infinite(self) -> bool {
+        self == WaitMs::INFINITE
+    }
+}
+
+/// Converts `duration` to milliseconds for a wait, rounding up so the wait
+/// never ends before `duration` has passed. Durations too long to express
+/// saturate to `u32::MAX`, the `INFINITE` sentinel of the Windows waits.
+pub fn duration_to_wait_ms(duration: Duration) -> u32 {
+    let ms = duration.as_nanos().div_ceil(1_000_000);
+    if ms >= u128::from(u32::MAX) {
+        u32::MAX
+    } else {
+        ms as u32
+    }
+}
+
+/// The inverse of `duration_to_wait_ms`: `None` for the `INFINITE`
+/// sentinel.
+pub fn wait_ms_to_duration(ms: u32) -> Option<Duration> {
+    if ms == u32::MAX {
+        None
+    } else {
+        Some(Duration::from_millis(u64::from(ms)))
+    }
+}
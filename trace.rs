@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use super::Event;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TraceOp {
+    Notify,
+    Unnotify,
+    /// A `wait`, `wait_for` or `wait_until` call returned.
+    Wait,
+}
+
+/// One recorded operation; see `export_trace`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub op: TraceOp,
+    /// The address of the event; stable while it lives.
+    pub event: usize,
+    pub thread: ThreadId,
+    /// Time since the first recorded operation.
+    pub at: Duration,
+}
+
+struct Recorder {
+    start: Option<Instant>,
+    events: Vec<TraceEvent>,
+}
+
+static RECORDER: Mutex<Recorder> = Mutex::new(Recorder {
+    start: None,
+    events: Vec::new(),
+});
+
+pub fn record(event: &Event, op: TraceOp) {
+    let now = Instant::now();
+    let mut recorder = RECORDER.lock().unwrap();
+    let start = *recorder.start.get_or_insert(now);
+    recorder.events.push(TraceEvent {
+        op: op,
+        event: event as *const Event as usize,
+        thread: thread::current().id(),
+        at: now - start,
+    });
+}
+
+/// Returns every operation recorded so far, in order.
+pub fn export_trace() -> Vec<TraceEvent> {
+    RECORDER.lock().unwrap().events.clone()
+}
+
+/// Re-applies the notifies and resets of `trace` on `events`, at the same
+/// intervals as they were recorded, returning once the last one is done.
+///
+/// The events of the trace are mapped to `events` in the order they first
+/// appear in it; operations on events beyond the end of `events` are
+/// skipped, as are waits.
+pub fn replay(trace: &[TraceEvent], events: &[Arc<Event>]) {
+    let mut seen = Vec::new();
+    let offset = trace.first().map_or(Duration::from_secs(0), |first| {
+        first.at
+    });
+    let start = Instant::now();
+    for traced in trace {
+        let index = match seen.iter().position(|&event| event == traced.event) {
+            Some(index) => index,
+            None => {
+                seen.push(traced.event);
+                seen.len() - 1
+            },
+        };
+        let event = match events.get(index) {
+            Some(event) if traced.op != TraceOp::Wait => event,
+            _ => continue,
+        };
+        let due = start + (traced.at - offset);
+        let now = Instant::now();
+        if due > now {
+            thread::sleep(due - now);
+        };
+        match traced.op {
+            TraceOp::Notify => event.notify(),
+            TraceOp::Unnotify => event.unnotify(),
+            TraceOp::Wait => {},
+        };
+    };
+}
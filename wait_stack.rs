@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, ThreadId};
+
+use super::Event;
+
+type Stack = Mutex<Vec<WaitFrame>>;
+
+/// One entry of a thread's wait stack, pushed by `Event::wait_labeled`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WaitFrame {
+    /// The address of the event waited on; stable while it is waited on.
+    pub event: usize,
+    pub label: String,
+}
+
+// Every thread that ever called `wait_labeled`. A thread's stack is dropped
+// with its thread-local, which leaves a dead entry behind until the next
+// dump.
+static STACKS: Mutex<Vec<(ThreadId, Weak<Stack>)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static STACK: Arc<Stack> = {
+        let stack = Arc::new(Mutex::new(Vec::new()));
+        STACKS.lock().unwrap().push(
+            (thread::current().id(), Arc::downgrade(&stack))
+        );
+        stack
+    };
+}
+
+/// Returns a snapshot of what every thread is blocked on in
+/// `Event::wait_labeled`, innermost wait last. Threads not currently waiting
+/// are left out. The snapshot is best-effort: waits may begin or end while
+/// it is taken.
+pub fn dump_wait_stacks() -> Vec<(ThreadId, Vec<WaitFrame>)> {
+    let mut stacks = STACKS.lock().unwrap();
+    stacks.retain(|(_, stack)| stack.strong_count() > 0);
+    stacks.iter().filter_map(|&(id, ref stack)| {
+        let frames = stack.upgrade()?.lock().unwrap().clone();
+        if frames.is_empty() {
+            None
+        } else {
+            Some((id, frames))
+        }
+    }).collect()
+}
+
+// Pops the frame again even if the wait unwinds.
+struct PopFrame;
+
+impl Drop for PopFrame {
+    fn drop(&mut self) {
+        STACK.with(|stack| stack.lock().unwrap().pop());
+    }
+}
+
+impl Event {
+    /// Waits like `wait`, with a frame naming the event and `label` on the
+    /// current thread's wait stack for as long as it blocks; see
+    /// `dump_wait_stacks`.
+    pub fn wait_labeled(&self, label: &str) {
+        let frame = WaitFrame {
+            event: self as *const Event as usize,
+            label: label.to_owned(),
+        };
+        STACK.with(|stack| stack.lock().unwrap().push(frame));
+        let _pop = PopFrame;
+        self.wait();
+    }
+}
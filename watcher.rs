@@ -0,0 +1,29 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use super::{Event, WaitTimeoutResult};
+
+/// Observes an event without keeping it alive. Created by
+/// `Event::weak_watcher`.
+#[derive(Clone)]
+pub struct WeakWatcher {
+    event: Weak<Event>,
+}
+
+impl Event {
+    pub fn weak_watcher(self: &Arc<Self>) -> WeakWatcher {
+        WeakWatcher { event: Arc::downgrade(self) }
+    }
+}
+
+impl WeakWatcher {
+    /// Waits like `Event::wait_for`, or returns `None` if the event has
+    /// already been dropped. The event is kept alive until the wait returns.
+    pub fn try_wait_for(&self, timeout: Duration) -> Option<WaitTimeoutResult> {
+        self.event.upgrade().map(|event| event.wait_for(timeout))
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.event.strong_count() > 0
+    }
+}
@@ -1,25 +1,98 @@
 extern crate winapi;
 extern crate kernel32;
-extern crate chrono;
+extern crate winmm;
+#[cfg(feature = "async-std")]
+extern crate async_std;
+#[cfg(feature = "mio")]
+extern crate mio;
+#[cfg(feature = "rand")]
+extern crate rand;
 
 use std::usize;
 
+use std::any::Any;
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::panic::Location;
+use std::mem;
+use std::ops::{BitOr, BitOrAssign};
+use std::os::raw::c_int;
 use std::ptr::{null, null_mut};
 use std::result::Result;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread::{self, Thread};
 use std::time::{Duration, Instant};
 
+#[cfg(feature = "async-std")]
+use std::future::Future;
+#[cfg(feature = "async-std")]
+use std::pin::Pin;
+use std::sync::{Condvar, Mutex};
+#[cfg(feature = "async-std")]
+use std::task::{Context, Poll};
+
 use self::winapi::winnt::{HANDLE, MAXIMUM_WAIT_OBJECTS};
-use self::winapi::winbase::{INFINITE, WAIT_OBJECT_0};
+use self::winapi::winnt::{DELETE, READ_CONTROL, SYNCHRONIZE};
+use self::winapi::winnt::{WRITE_DAC, WRITE_OWNER};
+use self::winapi::winbase::{INFINITE, WAIT_FAILED, WAIT_OBJECT_0};
+use self::winapi::winbase::THREAD_PRIORITY_ABOVE_NORMAL;
+use self::winapi::winbase::THREAD_PRIORITY_ERROR_RETURN;
 use self::winapi::winerror::WAIT_TIMEOUT;
 use self::winapi::minwindef::{BOOL, DWORD, TRUE, FALSE};
+use self::winapi::mmsystem::TIMERR_NOERROR;
 use self::kernel32::{CreateEventW, CloseHandle, WaitForSingleObject};
+use self::kernel32::CreateEventExW;
+use self::kernel32::{GetCurrentThread, GetThreadPriority, SetThreadPriority};
 use self::kernel32::{SetEvent, ResetEvent, WaitForMultipleObjects};
+use self::winmm::{timeBeginPeriod, timeEndPeriod};
+#[cfg(feature = "async-std")]
+use self::winapi::winnt::DUPLICATE_SAME_ACCESS;
+#[cfg(feature = "async-std")]
+use self::kernel32::{DuplicateHandle, GetCurrentProcess};
+#[cfg(feature = "async-std")]
+use self::async_std::task::{self, JoinHandle};
 
-use self::chrono::Duration as ChDuration;
+#[cfg(feature = "deadlock-detection")]
+use super::deadlock;
+#[cfg(feature = "registry")]
+use super::registry::RegistryEntry;
+#[cfg(feature = "trace")]
+use super::trace::{self, TraceOp};
+use super::timeout::{WaitMs, duration_to_wait_ms};
+use super::stats::{EventStats, NotifyIntervals, StatsCounters};
+use super::stats::{elapsed_nanos, next_notify_seq};
+use super::hierarchy::Children;
+use super::log::catch_callback_panic;
 
 pub struct Event {
     handle: HANDLE,
+    auto_reset: bool,
+    poisoned: AtomicBool,
+    generation: AtomicU64,
+    coalesced_generation: AtomicU64,
+    notify_seq: AtomicU64,
+    reset_generation: Mutex<u64>,
+    reset_condvar: Condvar,
+    holders: Mutex<usize>,
+    waiters: AtomicUsize,
+    redundant_notifies: AtomicU64,
+    stats: Option<Arc<StatsCounters>>,
+    max_wait_nanos: Option<AtomicU64>,
+    waiter_location: Option<Mutex<Option<&'static Location<'static>>>>,
+    notify_intervals: Option<NotifyIntervals>,
+    children: Children,
+    filtered_waiters: Mutex<Vec<(u64, Arc<Event>)>>,
+    #[cfg(feature = "mio")]
+    mio_wakers: Mutex<Vec<Arc<mio::Waker>>>,
+    unpark_threads: Mutex<Vec<Thread>>,
+    once_callbacks: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+    reply: Mutex<Option<Box<dyn Any + Send>>>,
+    reply_condvar: Condvar,
+    #[cfg(feature = "registry")]
+    registry_entry: Arc<RegistryEntry>,
 }
 
 unsafe impl Send for Event {}
@@ -40,19 +113,203 @@ impl Into<BOOL> for WaitFor {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct WaitTimeoutResult {
     timed_out: bool,
 }
 
 impl WaitTimeoutResult {
+    pub(crate) fn new(timed_out: bool) -> Self {
+        WaitTimeoutResult { timed_out: timed_out }
+    }
+
     pub fn timed_out(&self) -> bool {
         self.timed_out
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimedOut;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Poisoned;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TooManySpurious;
+
+const EVENT_MODIFY_STATE: DWORD = 0x0002;
+const EVENT_ALL_ACCESS: DWORD = 0x001F0003;
+const CREATE_EVENT_MANUAL_RESET: DWORD = 0x00000001;
+const CREATE_EVENT_INITIAL_SET: DWORD = 0x00000002;
+
+/// Access rights requested for an event handle, combined with `|`.
+///
+/// A handle without `SYNCHRONIZE` cannot be waited on: `wait_checked`
+/// through it returns an access-denied error, and the other waits panic
+/// with it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EventAccess {
+    bits: DWORD,
+}
+
+impl EventAccess {
+    pub const MODIFY_STATE: EventAccess =
+        EventAccess { bits: EVENT_MODIFY_STATE };
+    pub const SYNCHRONIZE: EventAccess = EventAccess { bits: SYNCHRONIZE };
+    pub const DELETE: EventAccess = EventAccess { bits: DELETE };
+    pub const READ_CONTROL: EventAccess = EventAccess { bits: READ_CONTROL };
+    pub const WRITE_DAC: EventAccess = EventAccess { bits: WRITE_DAC };
+    pub const WRITE_OWNER: EventAccess = EventAccess { bits: WRITE_OWNER };
+    pub const ALL: EventAccess = EventAccess { bits: EVENT_ALL_ACCESS };
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn contains(&self, other: EventAccess) -> bool {
+        self.bits & other.bits == other.bits
+    }
+}
+
+impl BitOr for EventAccess {
+    type Output = EventAccess;
+
+    fn bitor(self, rhs: EventAccess) -> EventAccess {
+        EventAccess { bits: self.bits | rhs.bits }
+    }
+}
+
+impl BitOrAssign for EventAccess {
+    fn bitor_assign(&mut self, rhs: EventAccess) {
+        self.bits |= rhs.bits;
+    }
+}
+
+/// Invalid input to a wait, returned by the `try_` variants of the waiting
+/// functions. Those never panic on bad input and are the robust choice for
+/// timeouts or event sets that come from outside the program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WaitError {
+    /// More events than the platform can wait on at once (Windows only).
+    TooManyObjects,
+    TimeoutTooLarge,
+    /// The deadline has already passed.
+    NegativeTimeout,
+    EmptySlice,
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            WaitError::TooManyObjects => "Too many events to wait for.",
+            WaitError::TimeoutTooLarge => "Time period too large.",
+            WaitError::NegativeTimeout => "Cannot wait for a previous time.",
+            WaitError::EmptySlice => "Cannot wait for an empty set of events.",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl Error for WaitError {}
+
+fn timeout_ms(timeout: Duration) -> Result<DWORD, WaitError> {
+    match duration_to_wait_ms(timeout) {
+        INFINITE => Err(WaitError::TimeoutTooLarge),
+        ms => Ok(ms),
+    }
+}
+
+// The panicking counterpart of `timeout_ms`, for the plain waits.
+fn checked_timeout_ms(timeout: Duration) -> DWORD {
+    timeout_ms(timeout).unwrap_or_else(|_e| {
+        panic!("Time period too large.");
+    })
+}
+
+fn deadline_ms(timeout: Instant) -> Result<DWORD, WaitError> {
+    let now = Instant::now();
+    if timeout < now {
+        return Err(WaitError::NegativeTimeout);
+    };
+    timeout_ms(timeout - now)
+}
+
+fn check_slice(slice: &[Arc<Event>]) -> Result<(), WaitError> {
+    if slice.is_empty() {
+        Err(WaitError::EmptySlice)
+    } else if slice.len() > MAXIMUM_WAIT_OBJECTS as usize {
+        Err(WaitError::TooManyObjects)
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Mark {
+    generation: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EventState {
+    pub signaled: bool,
+    pub auto_reset: bool,
+}
+
+#[derive(Debug)]
+pub enum EventError {
+    Os(io::Error),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WaitOutcome {
+    Signaled,
+    TimedOut,
+    Cancelled,
+    ShutdownRequested,
+    /// A callback run by the wait panicked; the event is left usable.
+    CallbackPanicked,
+}
+
+/// What happened during an `Event::wait_diagnostic` call.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WaitDiagnostic {
+    /// `Signaled` or `TimedOut`.
+    pub outcome: WaitOutcome,
+    /// Wakeups that found the event unsignaled and went back to waiting.
+    pub spurious_wakeups: u32,
+    pub elapsed: Duration,
+    /// Whether the event was already signaled on entry, so the call never
+    /// blocked.
+    pub fast_path: bool,
+}
+
+static GLOBAL_SHUTDOWN_EVENT: Mutex<Option<Arc<Event>>> = Mutex::new(None);
+
+/// Registers the event that `Event::wait_respecting_shutdown` treats as the
+/// process-wide shutdown signal, replacing any previous one.
+pub fn set_global_shutdown_event(event: Arc<Event>) {
+    *GLOBAL_SHUTDOWN_EVENT.lock().unwrap() = Some(event);
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EventError::Os(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for EventError {}
+
 impl Event {
     pub fn new(initial_signaled: bool, auto_reset: bool) -> Result<Self, ()> {
+        Event::create(initial_signaled, auto_reset).map_err(|_| ())
+    }
+
+    // `new`, keeping the error of `CreateEventW`.
+    fn create(initial_signaled: bool, auto_reset: bool) ->
+        Result<Self, EventError>
+    {
         let handle = unsafe {
             CreateEventW(
                 null_mut(),
@@ -62,29 +319,227 @@ impl Event {
             )
         };
         if handle == null_mut() {
-            Err(())
+            Err(EventError::Os(io::Error::last_os_error()))
+        } else {
+            Ok(Event::from_handle(handle, auto_reset))
+        }
+    }
+
+    pub fn new_with_access(
+        initial_signaled: bool,
+        auto_reset: bool,
+        access: EventAccess
+    ) -> Result<Self, EventError> {
+        let mut flags = 0;
+        if !auto_reset {
+            flags |= CREATE_EVENT_MANUAL_RESET;
+        };
+        if initial_signaled {
+            flags |= CREATE_EVENT_INITIAL_SET;
+        };
+        let handle = unsafe {
+            CreateEventExW(null_mut(), null(), flags, access.bits())
+        };
+        if handle == null_mut() {
+            Err(EventError::Os(io::Error::last_os_error()))
         } else {
-            Ok(Event{ handle: handle })
+            Ok(Event::from_handle(handle, auto_reset))
         }
     }
 
+    /// Rebuilds an event from a state captured with `capture_state`.
+    pub fn from_state(state: EventState) -> Result<Self, EventError> {
+        Event::create(state.signaled, state.auto_reset)
+    }
+
+    /// Captures the logical state of the event without consuming a signal.
+    pub fn capture_state(&self) -> EventState {
+        EventState {
+            signaled: self.peek(),
+            auto_reset: self.auto_reset,
+        }
+    }
+
+    fn from_handle(handle: HANDLE, auto_reset: bool) -> Self {
+        // The probe outlives neither the event nor its handle; see `Drop`.
+        #[cfg(feature = "registry")]
+        let probed = handle as usize;
+        Event {
+            handle: handle,
+            auto_reset: auto_reset,
+            poisoned: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            coalesced_generation: AtomicU64::new(0),
+            notify_seq: AtomicU64::new(0),
+            reset_generation: Mutex::new(0),
+            reset_condvar: Condvar::new(),
+            holders: Mutex::new(0),
+            waiters: AtomicUsize::new(0),
+            redundant_notifies: AtomicU64::new(0),
+            stats: None,
+            max_wait_nanos: None,
+            waiter_location: None,
+            notify_intervals: None,
+            children: Children::default(),
+            filtered_waiters: Mutex::new(Vec::new()),
+            #[cfg(feature = "mio")]
+            mio_wakers: Mutex::new(Vec::new()),
+            unpark_threads: Mutex::new(Vec::new()),
+            once_callbacks: Mutex::new(Vec::new()),
+            reply: Mutex::new(None),
+            reply_condvar: Condvar::new(),
+            #[cfg(feature = "registry")]
+            registry_entry: RegistryEntry::register(
+                auto_reset,
+                Box::new(move || unsafe { peek_handle(probed, auto_reset) })
+            ),
+        }
+    }
+
+    pub(crate) fn enable_stats(&mut self) {
+        self.stats = Some(Arc::new(StatsCounters::default()));
+    }
+
+    pub(crate) fn reserve_waiters(&mut self, _capacity: usize) {}
+
+    /// Always zero: waiters are tracked by the kernel, not by the event.
+    pub fn waiter_capacity(&self) -> usize {
+        0
+    }
+
+    fn add_waiter(&self) {
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "registry")]
+        self.registry_entry.add_waiter();
+    }
+
+    fn remove_waiter(&self) {
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        #[cfg(feature = "registry")]
+        self.registry_entry.remove_waiter();
+    }
+
+    #[cfg(feature = "registry")]
+    pub(crate) fn registry_entry(&self) -> &RegistryEntry {
+        &self.registry_entry
+    }
+
+    /// Whether any thread is currently blocked in one of the event's waits,
+    /// alone or as part of a multi-event wait. Pending `wait_async_std`
+    /// futures are not counted. Cheaper than counting the waiters, and just
+    /// as stale by the time it returns.
+    pub fn has_waiters(&self) -> bool {
+        self.waiters.load(Ordering::Relaxed) > 0
+    }
+
+    pub(crate) fn track_max_wait(&mut self) {
+        self.max_wait_nanos = Some(AtomicU64::new(0));
+    }
+
+    pub(crate) fn track_waiter_locations(&mut self) {
+        self.waiter_location = Some(Mutex::new(None));
+    }
+
+    /// Where the last plain wait on the event was called from, on events
+    /// built with `EventBuilder::track_waiter_locations(true)`. Covers `wait`,
+    /// `wait_for`, `wait_until` and their variants; `None` until the first
+    /// of them.
+    pub fn last_waiter_location(&self) -> Option<&'static Location<'static>> {
+        *self.waiter_location.as_ref()?.lock().unwrap()
+    }
+
+    #[track_caller]
+    fn record_location(&self) {
+        if let Some(ref location) = self.waiter_location {
+            *location.lock().unwrap() = Some(Location::caller());
+        };
+    }
+
+    pub(crate) fn children(&self) -> &Children {
+        &self.children
+    }
+
+    pub(crate) fn track_notify_intervals(&mut self) {
+        self.notify_intervals = Some(NotifyIntervals::default());
+    }
+
+    /// A guess at how long until the next `notify`, on events built with
+    /// `EventBuilder::track_notify_intervals(true)`: the moving average of
+    /// the time between past notifies, minus the time since the last one,
+    /// and zero once that average has passed.
+    ///
+    /// Only a heuristic for ordering waits, e.g. across events that fire at
+    /// a steady rate. `None` until a few notifies have been seen, and always
+    /// for any other event.
+    pub fn estimated_time_to_next(&self) -> Option<Duration> {
+        self.notify_intervals.as_ref()?.estimated_time_to_next()
+    }
+
+    /// The longest any single `wait`, `wait_for` or `wait_until` call has
+    /// blocked, on events built with `EventBuilder::track_max_wait(true)`.
+    /// Zero for any other event.
+    pub fn max_wait_duration(&self) -> Duration {
+        match self.max_wait_nanos {
+            Some(ref max) => Duration::from_nanos(max.load(Ordering::Relaxed)),
+            None => Duration::from_secs(0),
+        }
+    }
+
+    pub fn reset_max_wait(&self) {
+        if let Some(ref max) = self.max_wait_nanos {
+            max.store(0, Ordering::Relaxed);
+        };
+    }
+
+    /// Returns the aggregate counters of the event; see `EventStats`.
+    pub fn stats(&self) -> EventStats {
+        EventStats::new(self.stats.clone().unwrap_or_default())
+    }
+
+    #[track_caller]
     pub fn wait(&self) {
         self.wait_ms(INFINITE);
     }
 
+    #[track_caller]
     pub fn wait_for(&self, timeout: Duration) -> WaitTimeoutResult {
-        let ms = ChDuration::from_std(timeout).unwrap_or_else(|_e| {
-            panic!("Time period too large.");
-        }).num_milliseconds();
-        if ms < 0 {
-            panic!("Cannot wait for a negative time period.");
-        };
-        if ms >= INFINITE as i64 {
-            panic!("Time period too large.");
-        };
-        self.wait_ms(ms as DWORD)
+        self.wait_ms(checked_timeout_ms(timeout))
+    }
+
+    /// Waits like `wait` and returns the sequence number of the last
+    /// `notify` of the event. Every notify, on any event, takes the next
+    /// number of a single process-wide counter, so comparing the numbers
+    /// returned for several events tells in which order they were notified.
+    ///
+    /// The number is stored before the event is set, so a notify racing
+    /// with the wake-up may be reported instead of the one that woke it.
+    pub fn wait_seq(&self) -> u64 {
+        self.wait_ms(INFINITE);
+        self.notify_seq.load(Ordering::SeqCst)
+    }
+
+    /// Waits like `wait_for` for a timeout in whole milliseconds, or like
+    /// `wait` for `WaitMs::INFINITE`.
+    #[track_caller]
+    pub fn wait_for_ms(&self, timeout: WaitMs) -> WaitTimeoutResult {
+        self.wait_ms(timeout.0)
     }
 
+    /// Like `wait_for`, but raises the system timer resolution to 1ms while
+    /// waiting so that short timeouts expire on time instead of on the next
+    /// default tick, about 15.6ms away.
+    ///
+    /// The timer resolution is a system-wide setting: while it is raised,
+    /// every process on the machine is woken more often, which costs power
+    /// and some throughput. Reserve this for short waits on latency-critical
+    /// paths.
+    #[track_caller]
+    pub fn wait_for_high_res(&self, timeout: Duration) -> WaitTimeoutResult {
+        let _resolution = TimerResolution::raise();
+        self.wait_for(timeout)
+    }
+
+    #[track_caller]
     pub fn wait_until(&self, timeout: Instant) -> WaitTimeoutResult {
         let now = Instant::now();
         if timeout < now {
@@ -93,25 +548,799 @@ impl Event {
         self.wait_for(timeout - now)
     }
 
+    /// Like `wait_for`, but reports an invalid timeout as a `WaitError`
+    /// instead of panicking.
+    #[track_caller]
+    pub fn try_wait_for(&self, timeout: Duration) ->
+        Result<WaitTimeoutResult, WaitError>
+    {
+        Ok(self.wait_ms(timeout_ms(timeout)?))
+    }
+
+    /// Like `wait_until`, but reports an invalid deadline as a `WaitError`
+    /// instead of panicking.
+    #[track_caller]
+    pub fn try_wait_until(&self, timeout: Instant) ->
+        Result<WaitTimeoutResult, WaitError>
+    {
+        Ok(self.wait_ms(deadline_ms(timeout)?))
+    }
+
+    #[track_caller]
     fn wait_ms(&self, ms: DWORD) -> WaitTimeoutResult {
-        let mut result = INFINITE;
-        while result != WAIT_OBJECT_0 && result != WAIT_TIMEOUT {
-            result = unsafe { WaitForSingleObject(self.handle, ms) };
+        #[cfg(feature = "deadlock-detection")]
+        let _blocked = deadlock::blocked_on(self);
+        let start = self.wait_started();
+        self.record_location();
+        self.add_waiter();
+        let result = unsafe { WaitForSingleObject(self.handle, ms) };
+        let failure = wait_failure(result);
+        self.remove_waiter();
+        if let Some(error) = failure {
+            panic!("Failed to wait for the event: {}.", error);
         };
+        self.record_wait(start, result == WAIT_TIMEOUT);
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Wait);
         WaitTimeoutResult { timed_out: result == WAIT_TIMEOUT }
     }
 
+    /// Waits like `wait_for`, or like `wait` when `timeout` is `None`, and
+    /// reports how the wait went; see `WaitDiagnostic`.
+    ///
+    /// The kernel does not wake waiters spuriously, so `spurious_wakeups` is
+    /// always zero here.
+    pub fn wait_diagnostic(&self, timeout: Option<Duration>) ->
+        WaitDiagnostic
+    {
+        let recorded = self.wait_started();
+        let start = Instant::now();
+        let ms = timeout.map_or(INFINITE, checked_timeout_ms);
+        let mut result = unsafe { WaitForSingleObject(self.handle, 0) };
+        let fast_path = result == WAIT_OBJECT_0;
+        if !fast_path {
+            self.add_waiter();
+            result = unsafe { WaitForSingleObject(self.handle, ms) };
+            let failure = wait_failure(result);
+            self.remove_waiter();
+            if let Some(error) = failure {
+                panic!("Failed to wait for the event: {}.", error);
+            };
+        };
+        let timed_out = result == WAIT_TIMEOUT;
+        self.record_wait(recorded, timed_out);
+        WaitDiagnostic {
+            outcome: if timed_out {
+                WaitOutcome::TimedOut
+            } else {
+                WaitOutcome::Signaled
+            },
+            spurious_wakeups: 0,
+            elapsed: start.elapsed(),
+            fast_path: fast_path,
+        }
+    }
+
+    // Waits are only timed when stats or max-wait tracking need it.
+    fn wait_started(&self) -> Option<Instant> {
+        if self.stats.is_some() || self.max_wait_nanos.is_some() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    fn record_wait(&self, start: Option<Instant>, timed_out: bool) {
+        let start = match start {
+            Some(start) => start,
+            None => return,
+        };
+        if let Some(ref stats) = self.stats {
+            stats.record_wait(start, timed_out);
+        };
+        if let Some(ref max) = self.max_wait_nanos {
+            max.fetch_max(elapsed_nanos(start), Ordering::Relaxed);
+        };
+    }
+
+    /// Waits for the event like `wait`, but reports a failed wait (e.g. a
+    /// handle created without `SYNCHRONIZE` access) instead of panicking.
+    pub fn wait_checked(&self) -> Result<(), EventError> {
+        self.add_waiter();
+        let result = unsafe { WaitForSingleObject(self.handle, INFINITE) };
+        self.remove_waiter();
+        match wait_failure(result) {
+            Some(error) => Err(EventError::Os(error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Captures how many times the event has been notified so far, for a
+    /// later `wait_since`.
+    pub fn mark(&self) -> Mark {
+        Mark { generation: self.generation.load(Ordering::SeqCst) }
+    }
+
+    /// Blocks until the event has been notified at least once since `mark`
+    /// was taken, returning immediately if that already happened. A signal
+    /// still pending on an auto-reset event is consumed.
+    ///
+    /// Event handles cannot wait on the notify count itself, so an event
+    /// that was already signaled when the mark was taken also satisfies the
+    /// wait.
+    pub fn wait_since(&self, mark: Mark) {
+        if self.generation.load(Ordering::SeqCst) != mark.generation {
+            self.try_wait();
+        } else {
+            self.wait();
+        };
+    }
+
+    /// Blocks until the event is signaled and returns how many notifies
+    /// arrived since the previous `wait_coalesced` (or since the event was
+    /// created), consuming the signal if auto-reset.
+    ///
+    /// For a single consumer of an auto-reset event whose producers may
+    /// signal faster than it drains: the count tells how many signals were
+    /// merged into this one. Every event counts its notifies, so there is no
+    /// counting mode to enable first.
+    ///
+    /// `notify` counts before it sets the event, so a signal can be counted
+    /// by one call and consumed by the next; a wakeup with nothing new to
+    /// count is skipped.
+    pub fn wait_coalesced(&self) -> usize {
+        loop {
+            self.wait();
+            let generation = self.generation.load(Ordering::SeqCst);
+            let last =
+                self.coalesced_generation.swap(generation, Ordering::SeqCst);
+            if generation != last {
+                return (generation - last) as usize;
+            };
+        };
+    }
+
+    /// Waits until either this event or `cancel` is signaled. Cancellation
+    /// wins if both are.
+    pub fn wait_cancellable(&self, cancel: &Event) -> WaitOutcome {
+        match wait_for_all_or_any_ms(&[cancel, self], WaitFor::Any, INFINITE) {
+            0 => WaitOutcome::Cancelled,
+            _ => WaitOutcome::Signaled,
+        }
+    }
+
+    /// Waits like `wait_cancellable`, cancelled by the event registered with
+    /// `set_global_shutdown_event`. Without one, this is a plain `wait`.
+    ///
+    /// The registry is read once on entry; a shutdown event registered while
+    /// already waiting is not observed.
+    pub fn wait_respecting_shutdown(&self) -> WaitOutcome {
+        let shutdown = GLOBAL_SHUTDOWN_EVENT.lock().unwrap().clone();
+        match shutdown {
+            Some(shutdown) => match self.wait_cancellable(&shutdown) {
+                WaitOutcome::Cancelled => WaitOutcome::ShutdownRequested,
+                outcome => outcome,
+            },
+            None => {
+                self.wait();
+                WaitOutcome::Signaled
+            },
+        }
+    }
+
+    // Probing an auto-reset event consumes its signal, so put it back.
+    fn peek(&self) -> bool {
+        unsafe { peek_handle(self.handle as usize, self.auto_reset) }
+    }
+
+    /// Returns `true` right away if `flag` is set, otherwise waits for the
+    /// event and returns `false`.
+    ///
+    /// For producers that set `flag` with `Release` ordering and then call
+    /// `notify`. A notify landing between the check and the wait leaves the
+    /// event signaled, so the wait returns at once.
+    pub fn wait_unless(&self, flag: &AtomicBool) -> bool {
+        if flag.load(Ordering::Acquire) {
+            return true;
+        };
+        self.wait();
+        false
+    }
+
+    /// Waits like `wait`, with the calling thread's scheduling priority
+    /// raised while it is parked so that it runs promptly once woken. The
+    /// previous priority is restored before returning, also on panic.
+    ///
+    /// The thread is raised to `THREAD_PRIORITY_ABOVE_NORMAL` within its
+    /// process's priority class, which needs no special privilege; threads
+    /// already at or above it are left alone.
+    ///
+    /// A boosted waiter holding a resource that lower-priority threads need
+    /// can cause priority inversion; keep the boost to waiters that hold no
+    /// locks.
+    pub fn wait_boosted(&self) {
+        let _boost = PriorityBoost::raise();
+        self.wait();
+    }
+
+    pub fn try_wait(&self) -> bool {
+        unsafe { WaitForSingleObject(self.handle, 0) == WAIT_OBJECT_0 }
+    }
+
+    /// Checks the event and, if it isn't signaled, hands control to
+    /// `yield_fn` before checking once more. Returns whether the event was
+    /// signaled (and consumed, if auto-reset).
+    ///
+    /// Meant for cooperative schedulers that must not block the OS thread;
+    /// `yield_fn` switches to another task.
+    pub fn poll_or_yield<F: FnMut()>(&self, mut yield_fn: F) -> bool {
+        if self.try_wait() {
+            return true;
+        };
+        yield_fn();
+        self.try_wait()
+    }
+
+    /// Waits for the event, giving up once more than `max_spurious` waits
+    /// have returned without it being signaled. The kernel does not wake
+    /// waiters spuriously, so this always waits like `wait`.
+    pub fn wait_bounded_spurious(&self, _max_spurious: u32) ->
+        Result<(), TooManySpurious>
+    {
+        self.wait();
+        Ok(())
+    }
+
+    /// Waits for the event, panicking with `msg` if `timeout` elapses first.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use event_object::Event;
+    ///
+    /// let event = Event::new(true, false).unwrap();
+    /// event.expect_within(Duration::from_millis(200), "event not signaled");
+    /// ```
+    pub fn expect_within(&self, timeout: Duration, msg: &str) {
+        if self.wait_for(timeout).timed_out() {
+            panic!("{}", msg);
+        };
+    }
+
+    pub fn require_within(&self, timeout: Duration) -> Result<(), TimedOut> {
+        if self.wait_for(timeout).timed_out() {
+            Err(TimedOut)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn notify(&self) {
-        let result = unsafe { SetEvent(self.handle) };
-        assert!(result != 0);
+        assert!(self.notify_with(0, Duration::from_secs(0), Event::set_event)
+            .is_ok());
+    }
+
+    /// Notifies the event like `notify`, but retries a failed `SetEvent` up
+    /// to `retries` times, `backoff` apart, for signals that must get
+    /// through in low-resource conditions. Returns the last error if every
+    /// attempt failed, in which case nobody is woken.
+    pub fn notify_robust(&self, retries: u32, backoff: Duration) ->
+        Result<(), io::Error>
+    {
+        self.notify_with(retries, backoff, Event::set_event)
+    }
+
+    pub(crate) fn set_event(&self) -> Result<(), io::Error> {
+        if unsafe { SetEvent(self.handle) } == 0 {
+            return Err(io::Error::last_os_error());
+        };
+        Ok(())
+    }
+
+    // Notifies through `set_event`, which tests replace to inject failures.
+    pub(crate) fn notify_with<F>(
+        &self,
+        retries: u32,
+        backoff: Duration,
+        mut set_event: F
+    ) -> Result<(), io::Error>
+        where F: FnMut(&Event) -> Result<(), io::Error>
+    {
+        #[cfg(feature = "deadlock-detection")]
+        deadlock::record_notify(self);
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Notify);
+        if self.peek() {
+            self.redundant_notifies.fetch_add(1, Ordering::SeqCst);
+        };
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.notify_seq.store(next_notify_seq(), Ordering::SeqCst);
+        if let Some(ref stats) = self.stats {
+            stats.record_notify();
+        };
+        if let Some(ref intervals) = self.notify_intervals {
+            intervals.record_notify();
+        };
+        let mut attempts = 0;
+        while let Err(error) = set_event(self) {
+            if attempts == retries {
+                return Err(error);
+            };
+            attempts += 1;
+            thread::sleep(backoff);
+        };
+        #[cfg(feature = "registry")]
+        self.registry_entry.state_changed();
+        for thread in self.unpark_threads.lock().unwrap().iter() {
+            thread.unpark();
+        };
+        #[cfg(feature = "mio")]
+        for waker in self.mio_wakers.lock().unwrap().iter() {
+            let _ = waker.wake();
+        };
+        let callbacks = mem::take(&mut *self.once_callbacks.lock().unwrap());
+        for callback in callbacks {
+            catch_callback_panic("notify_once callback", callback);
+        };
+        Ok(())
+    }
+
+    /// Waits like `wait`, but registered under `key`, so that it is also
+    /// woken by a `notify_except` for any other key.
+    pub fn wait_filtered(&self, key: u64) {
+        let private = Arc::new(Event::new(false, true).unwrap());
+        self.filtered_waiters.lock().unwrap().push((key, private.clone()));
+        self.add_waiter();
+        wait_for_all_or_any_ms(&[self, &*private], WaitFor::Any, INFINITE);
+        self.remove_waiter();
+        self.filtered_waiters.lock().unwrap().retain(|(_, event)| {
+            !Arc::ptr_eq(event, &private)
+        });
+    }
+
+    /// Wakes every thread blocked in `wait_filtered` with a key other than
+    /// `exclude_key`, e.g. all but the thread that caused the event.
+    ///
+    /// The event itself is left as it is: plain waiters are not woken, and
+    /// a later `wait_filtered` does not see this call.
+    pub fn notify_except(&self, exclude_key: u64) {
+        let waiters = self.filtered_waiters.lock().unwrap();
+        for &(key, ref private) in waiters.iter() {
+            if key != exclude_key {
+                private.notify();
+            };
+        };
+    }
+
+    /// Makes every `notify` also unpark `thread`, for threads that wait with
+    /// `wait_via_park`. The registration lasts as long as the event.
+    pub fn register_unpark(&self, thread: Thread) {
+        self.unpark_threads.lock().unwrap().push(thread);
+    }
+
+    /// Makes every `notify` also wake `waker`, so that a mio `Poll` can
+    /// treat the event as a wakeup source. The registration lasts as long
+    /// as the event; errors from `Waker::wake` are ignored.
+    #[cfg(feature = "mio")]
+    pub fn link_mio_waker(&self, waker: Arc<mio::Waker>) {
+        self.mio_wakers.lock().unwrap().push(waker);
+    }
+
+    /// Waits by parking the current thread until the event is signaled,
+    /// consuming the signal if auto-reset. The thread must have been passed
+    /// to `register_unpark` first, or it is never woken.
+    pub fn wait_via_park(&self) {
+        while !self.try_wait() {
+            thread::park();
+        };
+    }
+
+    /// Runs `f` once, on the next `notify`, then discards it.
+    ///
+    /// A signal already pending at registration doesn't count: `f` waits for
+    /// a `notify` that comes after this call. Callbacks run on the notifying
+    /// thread once the event has been set, in registration order. A callback
+    /// that panics is logged through the `set_log_hook` hook and skipped:
+    /// the rest still run and `notify` returns normally.
+    pub fn notify_once<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.once_callbacks.lock().unwrap().push(Box::new(f));
+    }
+
+    /// Hands `value` to the thread blocked in `wait_for_reply` and notifies
+    /// the event.
+    ///
+    /// The slot holds a single reply, for one notifier answering one waiter;
+    /// panics if a reply is already pending.
+    pub fn notify_reply<T: Send + 'static>(&self, value: T) {
+        {
+            let mut reply = self.reply.lock().unwrap();
+            if reply.is_some() {
+                panic!("A reply is already pending.");
+            };
+            *reply = Some(Box::new(value));
+            self.reply_condvar.notify_all();
+        }
+        self.notify();
+    }
+
+    /// Waits for the event to be notified through `notify_reply`, then
+    /// returns the value passed to it. Plain notifies are ignored.
+    ///
+    /// Panics if the reply is not a `T`.
+    pub fn wait_for_reply<T: Send + 'static>(&self) -> T {
+        let mut reply = self.reply.lock().unwrap();
+        self.add_waiter();
+        let reply = loop {
+            if let Some(reply) = reply.take() {
+                break reply;
+            };
+            reply = self.reply_condvar.wait(reply).unwrap();
+        };
+        self.remove_waiter();
+        // The reply is stored before the event is set; consume that signal.
+        if self.auto_reset {
+            self.wait();
+        };
+        downcast_reply(reply)
     }
 
     pub fn unnotify(&self) {
+        #[cfg(feature = "trace")]
+        trace::record(self, TraceOp::Unnotify);
         let result = unsafe { ResetEvent(self.handle) };
         assert!(result != 0);
+        #[cfg(feature = "registry")]
+        self.registry_entry.state_changed();
+        *self.reset_generation.lock().unwrap() += 1;
+        self.reset_condvar.notify_all();
+    }
+
+    /// Clears the signaled state like `unnotify`, which also records it as
+    /// a falling edge for `wait_for_reset_edge`.
+    pub fn reset_with_notification(&self) {
+        self.unnotify();
+    }
+
+    /// How many explicit resets (`unnotify`, `reset_with_notification`, a
+    /// dropped `GateGuard` closing its gate) the event has seen. Consuming
+    /// the signal of an auto-reset event is not counted.
+    pub fn reset_generation(&self) -> u64 {
+        *self.reset_generation.lock().unwrap()
+    }
+
+    /// Blocks until the reset generation differs from `since_gen`, i.e.
+    /// until the event has been reset since `reset_generation` returned
+    /// `since_gen`, and returns the new generation.
+    pub fn wait_for_reset_edge(&self, since_gen: u64) -> u64 {
+        let mut generation = self.reset_generation.lock().unwrap();
+        self.add_waiter();
+        while *generation == since_gen {
+            generation = self.reset_condvar.wait(generation).unwrap();
+        };
+        self.remove_waiter();
+        *generation
+    }
+
+    /// Waits for a manual-reset event used as a gate, and returns a guard
+    /// that closes the gate with `unnotify` when dropped, also on panic.
+    ///
+    /// Guards are counted: with several concurrent holders the gate closes
+    /// when the last one is dropped.
+    pub fn wait_and_hold(&self) -> GateGuard<'_> {
+        assert!(!self.auto_reset, "wait_and_hold needs a manual-reset event.");
+        loop {
+            self.wait();
+            // The last guard closes the gate with `holders` locked, so the
+            // gate is rechecked under the lock before joining the holders.
+            let mut holders = self.holders.lock().unwrap();
+            if self.peek() {
+                *holders += 1;
+                return GateGuard { event: self };
+            };
+        };
+    }
+
+    /// Marks the event as poisoned and signals it, releasing every waiter.
+    ///
+    /// Meant for a producer that can no longer deliver the real signal, e.g.
+    /// because it panicked. `wait_poisonable` reports the poisoning; plain
+    /// waits just see the event as signaled.
+    pub fn poison(&self) {
+        self.poisoned.store(true, Ordering::SeqCst);
+        self.notify();
+    }
+
+    /// Counts the `notify` calls that found the event already signaled.
+    ///
+    /// On a manual-reset event these are wasted signals, usually a sign of
+    /// over-signaling. An auto-reset event is left signaled only until a
+    /// waiter consumes it, so there a redundant notify means a signal was
+    /// merged into one that no waiter had taken yet.
+    ///
+    /// The state is checked just before `SetEvent`, not atomically with it,
+    /// so two overlapping notifies may both go uncounted.
+    pub fn redundant_notify_count(&self) -> u64 {
+        self.redundant_notifies.load(Ordering::SeqCst)
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    /// Returns an endless iterator performing one `wait_for(timeout)` per
+    /// item, yielding `Signaled` or `TimedOut`.
+    pub fn outcomes(&self, timeout: Duration) -> Outcomes<'_> {
+        Outcomes {
+            event: self,
+            timeout: timeout,
+        }
+    }
+
+    pub fn wait_poisonable(&self) -> Result<(), Poisoned> {
+        if self.poisoned.load(Ordering::SeqCst) {
+            return Err(Poisoned);
+        };
+        self.wait();
+        if self.poisoned.load(Ordering::SeqCst) {
+            // An auto-reset event only releases one waiter per signal, so
+            // pass it on to the next one.
+            let result = unsafe { SetEvent(self.handle) };
+            assert!(result != 0);
+            return Err(Poisoned);
+        };
+        Ok(())
+    }
+
+    /// Returns a future that resolves once the event is signaled, for the
+    /// async-std runtime.
+    ///
+    /// A thread from async-std's `spawn_blocking` pool waits on the event's
+    /// handle for the future, so the executor threads stay free. Dropping
+    /// the future cancels that wait.
+    #[cfg(feature = "async-std")]
+    pub fn wait_async_std(&self) -> WaitAsyncStd<'_> {
+        WaitAsyncStd {
+            event: self,
+            wait: None,
+        }
+    }
+}
+
+pub struct Outcomes<'a> {
+    event: &'a Event,
+    timeout: Duration,
+}
+
+impl<'a> Iterator for Outcomes<'a> {
+    type Item = WaitOutcome;
+
+    fn next(&mut self) -> Option<WaitOutcome> {
+        if self.event.wait_for(self.timeout).timed_out() {
+            Some(WaitOutcome::TimedOut)
+        } else {
+            Some(WaitOutcome::Signaled)
+        }
+    }
+}
+
+// Raises the current thread to `THREAD_PRIORITY_ABOVE_NORMAL` until dropped.
+struct PriorityBoost {
+    previous: Option<c_int>,
+}
+
+impl PriorityBoost {
+    fn raise() -> Self {
+        let thread = unsafe { GetCurrentThread() };
+        let previous = unsafe { GetThreadPriority(thread) };
+        let target = THREAD_PRIORITY_ABOVE_NORMAL as c_int;
+        let raised = previous != THREAD_PRIORITY_ERROR_RETURN as c_int &&
+                     previous < target &&
+                     unsafe { SetThreadPriority(thread, target) } != 0;
+        PriorityBoost { previous: if raised { Some(previous) } else { None } }
+    }
+}
+
+impl Drop for PriorityBoost {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous {
+            unsafe { SetThreadPriority(GetCurrentThread(), previous) };
+        };
+    }
+}
+
+// Raises the system timer resolution to 1ms until dropped.
+struct TimerResolution {
+    raised: bool,
+}
+
+impl TimerResolution {
+    fn raise() -> Self {
+        TimerResolution {
+            raised: unsafe { timeBeginPeriod(1) } == TIMERR_NOERROR,
+        }
+    }
+}
+
+impl Drop for TimerResolution {
+    fn drop(&mut self) {
+        if self.raised {
+            unsafe { timeEndPeriod(1) };
+        };
+    }
+}
+
+pub struct GateGuard<'a> {
+    event: &'a Event,
+}
+
+impl<'a> Drop for GateGuard<'a> {
+    fn drop(&mut self) {
+        let mut holders = self.event.holders.lock().unwrap();
+        *holders -= 1;
+        if *holders == 0 {
+            self.event.unnotify();
+        };
+    }
+}
+
+#[cfg(feature = "async-std")]
+pub struct WaitAsyncStd<'a> {
+    event: &'a Event,
+    wait: Option<BlockingWait>,
+}
+
+#[cfg(feature = "async-std")]
+impl<'a> Future for WaitAsyncStd<'a> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let event = self.event;
+        if self.wait.is_none() {
+            let probe = unsafe { WaitForSingleObject(event.handle, 0) };
+            if probe == WAIT_OBJECT_0 {
+                return Poll::Ready(());
+            };
+            let wait = BlockingWait::start(event.handle, event.auto_reset);
+            self.wait = Some(wait.unwrap_or_else(|error| {
+                panic!("Failed to wait for the event: {}.", error)
+            }));
+        };
+        let thread = &mut self.wait.as_mut().unwrap().thread;
+        match Pin::new(thread).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(result) => {
+                self.wait = None;
+                if let Err(error) = result {
+                    panic!("Failed to wait for the event: {}.", error);
+                };
+                Poll::Ready(())
+            },
+        }
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl<'a> Drop for WaitAsyncStd<'a> {
+    fn drop(&mut self) {
+        if let Some(ref wait) = self.wait {
+            unsafe { SetEvent(wait.cancel.0) };
+        };
+    }
+}
+
+// A handle closed when dropped.
+#[cfg(feature = "async-std")]
+struct OwnedHandle(HANDLE);
+
+#[cfg(feature = "async-std")]
+unsafe impl Send for OwnedHandle {}
+#[cfg(feature = "async-std")]
+unsafe impl Sync for OwnedHandle {}
+
+#[cfg(feature = "async-std")]
+impl OwnedHandle {
+    fn duplicate(handle: HANDLE) -> io::Result<Self> {
+        let process = unsafe { GetCurrentProcess() };
+        let mut duplicate = null_mut();
+        let result = unsafe {
+            DuplicateHandle(
+                process,
+                handle,
+                process,
+                &mut duplicate,
+                0,
+                FALSE,
+                DUPLICATE_SAME_ACCESS
+            )
+        };
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        };
+        Ok(OwnedHandle(duplicate))
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl Drop for OwnedHandle {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+// A `spawn_blocking` thread waiting on duplicates of an event's handle and
+// of `cancel`, so that it outlives neither handle. Setting `cancel` ends it.
+#[cfg(feature = "async-std")]
+struct BlockingWait {
+    cancel: OwnedHandle,
+    thread: JoinHandle<io::Result<()>>,
+}
+
+#[cfg(feature = "async-std")]
+impl BlockingWait {
+    fn start(handle: HANDLE, auto_reset: bool) -> io::Result<Self> {
+        let cancel = unsafe { CreateEventW(null_mut(), TRUE, FALSE, null()) };
+        if cancel.is_null() {
+            return Err(io::Error::last_os_error());
+        };
+        let cancel = OwnedHandle(cancel);
+        let handles = (
+            OwnedHandle::duplicate(handle)?,
+            OwnedHandle::duplicate(cancel.0)?
+        );
+        let thread = task::spawn_blocking(move || {
+            let (event, cancelled) = handles;
+            let raw = [event.0, cancelled.0];
+            let result = unsafe {
+                WaitForMultipleObjects(2, raw.as_ptr(), FALSE, INFINITE)
+            };
+            if let Some(error) = wait_failure(result) {
+                return Err(error);
+            };
+            // The future was dropped just as the event released this wait,
+            // so pass the signal on to the next waiter.
+            let dropped = unsafe { WaitForSingleObject(cancelled.0, 0) };
+            if auto_reset && result == WAIT_OBJECT_0 &&
+               dropped == WAIT_OBJECT_0 {
+                unsafe { SetEvent(event.0) };
+            };
+            Ok(())
+        });
+        Ok(BlockingWait {
+            cancel: cancel,
+            thread: thread,
+        })
     }
 }
 
+// The error behind a failed wait. A wait through a handle without
+// `SYNCHRONIZE` fails every time, so it must not be retried.
+fn wait_failure(result: DWORD) -> Option<io::Error> {
+    if result == WAIT_FAILED {
+        Some(io::Error::last_os_error())
+    } else {
+        None
+    }
+}
+
+fn downcast_reply<T: 'static>(reply: Box<dyn Any + Send>) -> T {
+    match reply.downcast() {
+        Ok(reply) => *reply,
+        Err(_) => panic!("The reply is not of the expected type."),
+    }
+}
+
+// Reads the state of an event handle, restoring the signal that probing an
+// auto-reset event consumes. The handle is passed as a `usize` so that the
+// registry probe can capture it.
+unsafe fn peek_handle(handle: usize, auto_reset: bool) -> bool {
+    let handle = handle as HANDLE;
+    let signaled = WaitForSingleObject(handle, 0) == WAIT_OBJECT_0;
+    if signaled && auto_reset {
+        let result = SetEvent(handle);
+        assert!(result != 0);
+    };
+    signaled
+}
+
 pub fn wait_for_any(slice: &[Arc<Event>]) -> usize {
     wait_for_all_or_any_ms(&slice, WaitFor::Any, INFINITE) as usize
 }
@@ -120,6 +1349,104 @@ pub fn wait_for_all(slice: &[Arc<Event>]) {
     wait_for_all_or_any_ms(&slice, WaitFor::All, INFINITE);
 }
 
+/// Returns a bitmask with bit `i` set iff `slice[i]` is currently signaled.
+/// No signal is consumed.
+pub fn poll_any_mask(slice: &[Arc<Event>]) -> u64 {
+    if slice.len() > 64 {
+        panic!("Cannot poll more than 64 events.");
+    };
+    let mut mask = 0;
+    for (id, event_ref) in slice.iter().enumerate() {
+        if event_ref.peek() {
+            mask |= 1 << id;
+        };
+    };
+    mask
+}
+
+/// Waits for any of the events, returning the index of the signaled event
+/// with the highest priority. Ties go to the lowest index.
+pub fn wait_for_any_prioritized(slice: &[(Arc<Event>, u8)]) -> usize {
+    let events = slice.iter()
+                      .map(|(event_ref, _)| event_ref.clone())
+                      .collect::<Vec<_>>();
+    let woken = wait_for_any(&events);
+    let mut best = woken;
+    for (id, &(ref event_ref, priority)) in slice.iter().enumerate() {
+        let best_priority = slice[best].1;
+        let preferred = priority > best_priority ||
+                        (priority == best_priority && id < best);
+        if preferred && event_ref.peek() {
+            best = id;
+        };
+    };
+    if best != woken {
+        swap_signal(&slice[best].0, &slice[woken].0);
+    };
+    best
+}
+
+/// Waits like `wait_for_any`, then picks one of the events signaled at that
+/// point at random, with a probability proportional to its weight, and
+/// consumes its signal if auto-reset. Events of weight zero are only picked
+/// if every signaled event has weight zero.
+///
+/// Draws from the thread-local generator; use
+/// `wait_for_any_weighted_lottery_with` and a seeded generator for
+/// reproducible picks.
+#[cfg(feature = "rand")]
+pub fn wait_for_any_weighted_lottery(slice: &[(Arc<Event>, u32)]) -> usize {
+    wait_for_any_weighted_lottery_with(slice, &mut rand::thread_rng())
+}
+
+/// Like `wait_for_any_weighted_lottery`, drawing from `rng`.
+#[cfg(feature = "rand")]
+pub fn wait_for_any_weighted_lottery_with<R: rand::Rng>(
+    slice: &[(Arc<Event>, u32)],
+    rng: &mut R
+) -> usize {
+    let events = slice.iter()
+                      .map(|(event_ref, _)| event_ref.clone())
+                      .collect::<Vec<_>>();
+    let woken = wait_for_any(&events);
+    let ready = slice.iter()
+                     .enumerate()
+                     .filter(|&(id, (event_ref, _))| {
+                         id == woken || event_ref.peek()
+                     })
+                     .map(|(id, &(_, weight))| (id, u64::from(weight)))
+                     .collect::<Vec<_>>();
+    let total = ready.iter().map(|&(_, weight)| weight).sum::<u64>();
+    if total == 0 {
+        return woken;
+    };
+    let mut ticket = rng.gen_range(0, total);
+    let mut chosen = woken;
+    for (id, weight) in ready {
+        if ticket < weight {
+            chosen = id;
+            break;
+        };
+        ticket -= weight;
+    };
+    if chosen != woken {
+        swap_signal(&slice[chosen].0, &slice[woken].0);
+    };
+    chosen
+}
+
+// Waking consumed the signal of `woken` if it is auto-reset; takes the
+// signal of `chosen` instead and hands that one back.
+fn swap_signal(chosen: &Event, woken: &Event) {
+    if chosen.auto_reset {
+        unsafe { WaitForSingleObject(chosen.handle, 0) };
+    };
+    if woken.auto_reset {
+        let result = unsafe { SetEvent(woken.handle) };
+        assert!(result != 0);
+    };
+}
+
 pub fn wait_for_any_with(slice: &[Arc<Event>], timeout: Duration) ->
     Result<usize, WaitTimeoutResult>
 {
@@ -131,6 +1458,34 @@ pub fn wait_for_any_with(slice: &[Arc<Event>], timeout: Duration) ->
     }
 }
 
+const STREAMING_CHUNK_LEN: usize = 64;
+
+/// Waits for every event like `wait_for_all`, but pulls them from `events` in
+/// chunks of 64 so that memory use does not grow with the event count.
+///
+/// A chunk is only waited on after the previous one fully signaled, so every
+/// event has to stay signaled once set: this panics on auto-reset events.
+/// Returns once each event has been observed signaled, not necessarily all
+/// at the same time.
+pub fn wait_for_all_streaming<I>(events: I)
+    where I: Iterator<Item = Arc<Event>>
+{
+    let mut chunk = Vec::with_capacity(STREAMING_CHUNK_LEN);
+    for event in events {
+        if event.auto_reset {
+            panic!("Cannot stream-wait for an auto-reset event.");
+        };
+        chunk.push(event);
+        if chunk.len() == STREAMING_CHUNK_LEN {
+            wait_for_all(&chunk);
+            chunk.clear();
+        };
+    };
+    if !chunk.is_empty() {
+        wait_for_all(&chunk);
+    };
+}
+
 pub fn wait_for_all_with(slice: &[Arc<Event>], timeout: Duration) ->
     WaitTimeoutResult
 {
@@ -141,16 +1496,7 @@ pub fn wait_for_all_with(slice: &[Arc<Event>], timeout: Duration) ->
 fn wait_with(slice: &[Arc<Event>], wait_for: WaitFor, timeout: Duration) ->
     DWORD
 {
-    let ms = ChDuration::from_std(timeout).unwrap_or_else(|_e| {
-        panic!("Time period too large.");
-    }).num_milliseconds();
-    if ms < 0 {
-        panic!("Cannot wait for a negative time period.");
-    };
-    if ms >= INFINITE as i64 {
-        panic!("Time period too large.");
-    };
-    wait_for_all_or_any_ms(slice, wait_for, ms as DWORD)
+    wait_for_all_or_any_ms(slice, wait_for, checked_timeout_ms(timeout))
 }
 
 pub fn wait_for_any_until(slice: &[Arc<Event>], timeout: Instant) ->
@@ -181,19 +1527,95 @@ fn wait_until(slice: &[Arc<Event>], wait_for: WaitFor, timeout: Instant) ->
     wait_with(slice, wait_for, timeout - now)
 }
 
-fn wait_for_all_or_any_ms(slice: &[Arc<Event>], wait_for: WaitFor, ms: DWORD) ->
-    DWORD
+/// Like `wait_for_any`, but reports invalid input as a `WaitError` instead
+/// of panicking or blocking forever.
+pub fn try_wait_for_any(slice: &[Arc<Event>]) -> Result<usize, WaitError> {
+    check_slice(slice)?;
+    Ok(wait_for_any(slice))
+}
+
+/// Like `wait_for_any_with`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_any_with(slice: &[Arc<Event>], timeout: Duration) ->
+    Result<Result<usize, WaitTimeoutResult>, WaitError>
+{
+    check_slice(slice)?;
+    let ms = timeout_ms(timeout)?;
+    let result = wait_for_all_or_any_ms(slice, WaitFor::Any, ms);
+    if result == WAIT_TIMEOUT {
+        Ok(Err(WaitTimeoutResult { timed_out: true }))
+    } else {
+        Ok(Ok(result as usize))
+    }
+}
+
+/// Like `wait_for_any_until`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_any_until(slice: &[Arc<Event>], timeout: Instant) ->
+    Result<Result<usize, WaitTimeoutResult>, WaitError>
+{
+    check_slice(slice)?;
+    let ms = deadline_ms(timeout)?;
+    let result = wait_for_all_or_any_ms(slice, WaitFor::Any, ms);
+    if result == WAIT_TIMEOUT {
+        Ok(Err(WaitTimeoutResult { timed_out: true }))
+    } else {
+        Ok(Ok(result as usize))
+    }
+}
+
+/// Like `wait_for_all`, but reports invalid input as a `WaitError` instead
+/// of panicking or blocking forever.
+pub fn try_wait_for_all(slice: &[Arc<Event>]) -> Result<(), WaitError> {
+    check_slice(slice)?;
+    wait_for_all(slice);
+    Ok(())
+}
+
+/// Like `wait_for_all_with`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_all_with(slice: &[Arc<Event>], timeout: Duration) ->
+    Result<WaitTimeoutResult, WaitError>
+{
+    check_slice(slice)?;
+    let ms = timeout_ms(timeout)?;
+    let result = wait_for_all_or_any_ms(slice, WaitFor::All, ms);
+    Ok(WaitTimeoutResult { timed_out: result == WAIT_TIMEOUT })
+}
+
+/// Like `wait_for_all_until`, but reports invalid input as a `WaitError`
+/// instead of panicking.
+pub fn try_wait_for_all_until(slice: &[Arc<Event>], timeout: Instant) ->
+    Result<WaitTimeoutResult, WaitError>
 {
+    check_slice(slice)?;
+    let ms = deadline_ms(timeout)?;
+    let result = wait_for_all_or_any_ms(slice, WaitFor::All, ms);
+    Ok(WaitTimeoutResult { timed_out: result == WAIT_TIMEOUT })
+}
+
+fn wait_for_all_or_any_ms<E: Borrow<Event>>(
+    slice: &[E],
+    wait_for: WaitFor,
+    ms: DWORD
+) -> DWORD {
+    if slice.is_empty() {
+        panic!("Cannot wait for an empty set of events.");
+    };
     if slice.len() > MAXIMUM_WAIT_OBJECTS as usize {
         panic!("Cannot wait for more than {} events", slice.len())
     };
     let vec_handle = slice.iter()
-                            .map(|event_ref| event_ref.handle)
+                            .map(|event_ref| event_ref.borrow().handle)
                             .collect::<Vec<_>>();
     let slice_handle = &vec_handle;
     let mut result: DWORD = slice_handle.len() as DWORD;
     let len: DWORD = slice_handle.len() as DWORD;
-    while result >= len && result != WAIT_TIMEOUT {
+    for event_ref in slice {
+        event_ref.borrow().add_waiter();
+    };
+    let mut failure = None;
+    while result >= len && result != WAIT_TIMEOUT && failure.is_none() {
         result = unsafe {
             WaitForMultipleObjects(
                 len,
@@ -202,12 +1624,23 @@ fn wait_for_all_or_any_ms(slice: &[Arc<Event>], wait_for: WaitFor, ms: DWORD) ->
                 ms
             )
         };
+        failure = wait_failure(result);
+    };
+    for event_ref in slice {
+        event_ref.borrow().remove_waiter();
+    };
+    if let Some(error) = failure {
+        panic!("Failed to wait for the event: {}.", error);
     };
     result
 }
 
 impl Drop for Event {
     fn drop(&mut self) {
+        #[cfg(feature = "deadlock-detection")]
+        deadlock::forget(self);
+        #[cfg(feature = "registry")]
+        self.registry_entry.retire();
         unsafe { CloseHandle(self.handle); };
     }
 }